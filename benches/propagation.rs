@@ -0,0 +1,65 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use satellite::io::Satrec;
+use satellite::propogation::batch::SatrecBatch;
+use satellite::propogation::initl::InitlMethod;
+use satellite::propogation::sgp4::sgp4_with_cache;
+
+fn verification_catalog() -> Vec<Satrec> {
+    let lines = include_str!("../testdata/SGP4-VER.TLE")
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .collect::<Vec<&str>>();
+
+    lines
+        .chunks(2)
+        .filter_map(|pair| satellite::io::twoline2satrec(pair[0], &pair[1][..69]).ok())
+        .collect()
+}
+
+/// Repeat `satrecs` up to `len` objects.
+fn catalog_of(satrecs: &[Satrec], len: usize) -> Vec<Satrec> {
+    satrecs.iter().cycle().take(len).cloned().collect()
+}
+
+fn bench_catalog(c: &mut Criterion, name: &str, satrecs: &[Satrec]) {
+    let mut group = c.benchmark_group(name);
+
+    for &len in &[1_000, 30_000] {
+        let catalog = catalog_of(satrecs, len);
+        let batch = SatrecBatch::new(&catalog);
+        let tsince = vec![720.0; len];
+
+        group.throughput(Throughput::Elements(len as u64));
+
+        group.bench_with_input(BenchmarkId::new("sgp4", len), &catalog, |b, catalog| {
+            b.iter(|| {
+                catalog
+                    .iter()
+                    .map(|satrec| sgp4_with_cache(satrec, black_box(720.0), None))
+                    .collect::<Vec<_>>()
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("batch", len), &batch, |b, batch| {
+            b.iter(|| batch.propogate(black_box(&tsince)))
+        });
+    }
+
+    group.finish();
+}
+
+fn propagation(c: &mut Criterion) {
+    let catalog = verification_catalog();
+    let near_earth = catalog
+        .iter()
+        .filter(|satrec| satrec.method == InitlMethod::N)
+        .cloned()
+        .collect::<Vec<Satrec>>();
+
+    bench_catalog(c, "near-earth", &near_earth);
+    bench_catalog(c, "verification catalog", &catalog);
+}
+
+criterion_group!(benches, propagation);
+criterion_main!(benches);
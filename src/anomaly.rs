@@ -0,0 +1,318 @@
+//! Conversions between true, eccentric and mean anomaly, and Kepler's equation.
+//!
+//! As in Vallado, the "eccentric anomaly" of a hyperbolic orbit is the hyperbolic anomaly
+//! H, with M = e sinh H - H, and that of a parabolic orbit the parabolic anomaly
+//! B = tan(ν/2), with M = B + B³/3 (Barker's equation). Angles are in radians. Elliptic
+//! anomalies keep the revolution count of their input rather than being wrapped.
+
+use crate::constants::*;
+
+/// Eccentricities within this of one are treated as parabolic.
+const PARABOLIC: f64 = 1e-10;
+
+/// Iteration limit of the bracketed Newton solver; bisection alone converges within it.
+const MAX_ITERATIONS: usize = 200;
+
+#[derive(Debug, PartialEq)]
+pub enum AnomalyError {
+    /// The eccentricity is negative or not finite.
+    Eccentricity(f64),
+    /// The true anomaly lies beyond the asymptotes of a hyperbolic or parabolic orbit.
+    BeyondAsymptote(f64),
+}
+
+enum Conic {
+    Elliptic,
+    Parabolic,
+    Hyperbolic,
+}
+
+fn conic(ecc: f64) -> Result<Conic, AnomalyError> {
+    if !ecc.is_finite() || ecc < 0.0 {
+        Err(AnomalyError::Eccentricity(ecc))
+    } else if (ecc - 1.0).abs() < PARABOLIC {
+        Ok(Conic::Parabolic)
+    } else if ecc < 1.0 {
+        Ok(Conic::Elliptic)
+    } else {
+        Ok(Conic::Hyperbolic)
+    }
+}
+
+/// Splits an angle into whole revolutions and a remainder in [-π, π).
+fn revolutions(angle: f64) -> (f64, f64) {
+    let revs = ((angle + PI) / TWO_PI).floor();
+    (revs * TWO_PI, angle - revs * TWO_PI)
+}
+
+/// Newton's method on an increasing function, falling back to bisection whenever a step
+/// leaves the bracket `[lower, upper]` known to contain the root.
+fn bracketed_newton<F>(f: F, mut lower: f64, mut upper: f64, guess: f64) -> f64
+where
+    F: Fn(f64) -> (f64, f64),
+{
+    let mut x = guess.clamp(lower, upper);
+
+    for _ in 0..MAX_ITERATIONS {
+        let (value, slope) = f(x);
+        if value == 0.0 {
+            return x;
+        }
+        if value < 0.0 {
+            lower = x;
+        } else {
+            upper = x;
+        }
+
+        let newton = x - value / slope;
+        let next = if newton > lower && newton < upper {
+            newton
+        } else {
+            0.5 * (lower + upper)
+        };
+
+        if (next - x).abs() <= 4.0 * f64::EPSILON * x.abs().max(1.0) {
+            return next;
+        }
+        x = next;
+    }
+
+    x
+}
+
+/// Eccentric anomaly E (or H, or B) from the true anomaly.
+pub fn true_to_eccentric(nu: f64, ecc: f64) -> Result<f64, AnomalyError> {
+    match conic(ecc)? {
+        Conic::Elliptic => {
+            let (whole, nu) = revolutions(nu);
+            let (sinnu, cosnu) = nu.sin_cos();
+            let denom = 1.0 + ecc * cosnu;
+            let sine = (1.0 - ecc * ecc).sqrt() * sinnu / denom;
+            let cose = (ecc + cosnu) / denom;
+            Ok(whole + sine.atan2(cose))
+        }
+        Conic::Parabolic => {
+            if nu.abs() >= PI {
+                return Err(AnomalyError::BeyondAsymptote(nu));
+            }
+            Ok((nu / 2.0).tan())
+        }
+        Conic::Hyperbolic => {
+            if nu.abs() >= PI - (1.0 / ecc).acos() {
+                return Err(AnomalyError::BeyondAsymptote(nu));
+            }
+            let (sinnu, cosnu) = nu.sin_cos();
+            Ok(((ecc * ecc - 1.0).sqrt() * sinnu / (1.0 + ecc * cosnu)).asinh())
+        }
+    }
+}
+
+/// True anomaly from the eccentric anomaly E (or H, or B).
+pub fn eccentric_to_true(e0: f64, ecc: f64) -> Result<f64, AnomalyError> {
+    match conic(ecc)? {
+        Conic::Elliptic => {
+            let (whole, e0) = revolutions(e0);
+            let (sine, cose) = e0.sin_cos();
+            let denom = 1.0 - ecc * cose;
+            let sinnu = (1.0 - ecc * ecc).sqrt() * sine / denom;
+            let cosnu = (cose - ecc) / denom;
+            Ok(whole + sinnu.atan2(cosnu))
+        }
+        Conic::Parabolic => Ok(2.0 * e0.atan()),
+        Conic::Hyperbolic => {
+            let (sinhh, coshh) = (e0.sinh(), e0.cosh());
+            let denom = 1.0 - ecc * coshh;
+            let sinnu = -(ecc * ecc - 1.0).sqrt() * sinhh / denom;
+            let cosnu = (coshh - ecc) / denom;
+            Ok(sinnu.atan2(cosnu))
+        }
+    }
+}
+
+/// Mean anomaly from the eccentric anomaly E (or H, or B).
+pub fn eccentric_to_mean(e0: f64, ecc: f64) -> Result<f64, AnomalyError> {
+    match conic(ecc)? {
+        Conic::Elliptic => Ok(e0 - ecc * e0.sin()),
+        Conic::Parabolic => Ok(e0 + e0 * e0 * e0 / 3.0),
+        Conic::Hyperbolic => Ok(ecc * e0.sinh() - e0),
+    }
+}
+
+/// Solves Kepler's equation for the eccentric anomaly E (or H, or B) given the mean
+/// anomaly.
+///
+/// Elliptic and hyperbolic orbits use Newton's method safeguarded by bisection, which
+/// converges for every eccentricity including those close to one. Barker's equation for
+/// parabolic orbits is solved in closed form.
+pub fn mean_to_eccentric(m: f64, ecc: f64) -> Result<f64, AnomalyError> {
+    match conic(ecc)? {
+        Conic::Elliptic => {
+            let (whole, m) = revolutions(m);
+            if ecc == 0.0 {
+                return Ok(whole + m);
+            }
+
+            // |E - M| = e |sin E| <= e
+            let guess = if m < 0.0 { m - ecc } else { m + ecc };
+            let e0 = bracketed_newton(
+                |e0| (e0 - ecc * e0.sin() - m, 1.0 - ecc * e0.cos()),
+                m - ecc,
+                m + ecc,
+                guess,
+            );
+            Ok(whole + e0)
+        }
+        Conic::Parabolic => {
+            // B = y - 1/y with y³ = W + sqrt(W² + 1), W = 3M/2; odd in M.
+            let w = 1.5 * m.abs();
+            let y = (w + (w * w + 1.0).sqrt()).cbrt();
+            Ok((y - 1.0 / y).copysign(m))
+        }
+        Conic::Hyperbolic => {
+            // (e - 1) sinh H <= e sinh H - H <= e sinh H for H >= 0; odd in M.
+            let ma = m.abs();
+            let lower = (ma / ecc).asinh();
+            let upper = (ma / (ecc - 1.0)).asinh();
+            let h = bracketed_newton(
+                |h| (ecc * h.sinh() - h - ma, ecc * h.cosh() - 1.0),
+                lower,
+                upper,
+                lower,
+            );
+            Ok(h.copysign(m))
+        }
+    }
+}
+
+/// Mean anomaly from the true anomaly.
+pub fn true_to_mean(nu: f64, ecc: f64) -> Result<f64, AnomalyError> {
+    eccentric_to_mean(true_to_eccentric(nu, ecc)?, ecc)
+}
+
+/// True anomaly from the mean anomaly.
+pub fn mean_to_true(m: f64, ecc: f64) -> Result<f64, AnomalyError> {
+    eccentric_to_true(mean_to_eccentric(m, ecc)?, ecc)
+}
+
+/// Solves the equinoctial form of Kepler's equation used by SGP4,
+/// `u = x - axn sin x + ayn cos x` with `axn = e cos ω`, `ayn = e sin ω` and `x = E + ω`.
+///
+/// Returns the sine and cosine of `x` from the last iteration. This is the SGP4 iteration
+/// itself, with at most ten steps each limited to 0.95 rad, so results match the
+/// reference implementation exactly.
+pub fn kepler_equinoctial(u: f64, axn: f64, ayn: f64) -> (f64, f64) {
+    let mut x = u;
+    let mut sinx = 0.0;
+    let mut cosx = 0.0;
+    let mut step: f64 = 9999.9;
+    let mut ktr = 1;
+
+    //    sgp4fix for kepler iteration
+    //    the following iteration needs better limits on corrections
+    while step.abs() >= 1.0e-12 && ktr <= 10 {
+        sinx = x.sin();
+        cosx = x.cos();
+        step = 1.0 - (cosx * axn) - (sinx * ayn);
+        step = (((u - (ayn * cosx)) + (axn * sinx)) - x) / step;
+        if step.abs() >= 0.95 {
+            if step > 0.0 {
+                step = 0.95;
+            } else {
+                step = -0.95;
+            }
+        }
+        x += step;
+        ktr += 1;
+    }
+
+    (sinx, cosx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+
+    #[test]
+    fn test_kepler_vallado() {
+        // Vallado, Fundamentals of Astrodynamics and Applications, examples 2-1 and 2-3.
+        let e0 = mean_to_eccentric(235.4 * DEG_2_RAD, 0.4).unwrap();
+        assert_diff(e0 * RAD_TO_DEG, 220.512074767522, 1e-9);
+
+        let h = mean_to_eccentric(235.4 * DEG_2_RAD, 2.4).unwrap();
+        assert_diff(h, 1.601376144, 1e-9);
+    }
+
+    #[test]
+    fn test_kepler_residuals() {
+        let eccentricities = [
+            0.0, 1e-6, 0.1, 0.5, 0.9, 0.99, 0.999999, 1.0, 1.000001, 1.5, 5.0,
+        ];
+
+        for &ecc in &eccentricities {
+            for i in -40..=40 {
+                let m = f64::from(i) * 0.37;
+                let e0 = mean_to_eccentric(m, ecc).unwrap();
+                let residual = eccentric_to_mean(e0, ecc).unwrap() - m;
+                assert!(
+                    residual.abs() <= 1e-13 * m.abs().max(1.0),
+                    "ecc {} m {} residual {}",
+                    ecc,
+                    m,
+                    residual
+                );
+            }
+        }
+
+        // very large hyperbolic mean anomalies
+        let h = mean_to_eccentric(1e8, 1.2).unwrap();
+        assert_diff(eccentric_to_mean(h, 1.2).unwrap(), 1e8, 1e-6);
+    }
+
+    #[test]
+    fn test_true_round_trips() {
+        for &ecc in &[0.0f64, 0.3, 0.95, 1.0, 1.3, 4.0] {
+            let limit = if ecc < 1.0 {
+                3.0 * PI
+            } else {
+                PI - (1.0 / ecc).acos()
+            };
+
+            for i in -19..=19 {
+                let nu = f64::from(i) / 20.0 * limit;
+                let m = true_to_mean(nu, ecc).unwrap();
+                assert_diff(mean_to_true(m, ecc).unwrap(), nu, 1e-10);
+
+                let e0 = true_to_eccentric(nu, ecc).unwrap();
+                assert_diff(eccentric_to_true(e0, ecc).unwrap(), nu, 1e-10);
+            }
+        }
+
+        // the revolution count carries through elliptic conversions
+        let m = true_to_mean(2.0 * TWO_PI + 1.0, 0.2).unwrap();
+        assert_diff(m - 2.0 * TWO_PI, true_to_mean(1.0, 0.2).unwrap(), 1e-12);
+
+        assert_eq!(
+            true_to_mean(2.5, 2.0),
+            Err(AnomalyError::BeyondAsymptote(2.5))
+        );
+        assert_eq!(
+            true_to_mean(PI, 1.0),
+            Err(AnomalyError::BeyondAsymptote(PI))
+        );
+        assert_eq!(
+            mean_to_true(1.0, -0.1),
+            Err(AnomalyError::Eccentricity(-0.1))
+        );
+    }
+
+    #[test]
+    fn test_kepler_equinoctial() {
+        let (ecc, argp, m): (f64, f64, f64) = (0.1859667, 1.3, 4.0);
+        let (sinx, cosx) = kepler_equinoctial(m + argp, ecc * argp.cos(), ecc * argp.sin());
+
+        let e0 = mean_to_eccentric(m, ecc).unwrap();
+        assert_diff(sinx, (e0 + argp).sin(), 1e-12);
+        assert_diff(cosx, (e0 + argp).cos(), 1e-12);
+    }
+}
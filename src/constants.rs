@@ -9,9 +9,15 @@ pub static DEG_2_RAD: f64 = PI / 180.0;
 /// Conversion factorfor Radians -> Degrees
 pub static RAD_TO_DEG: f64 = 180.0 / PI;
 
+/// Conversion factor for Arcseconds -> Radians
+pub static ARCSEC_TO_RAD: f64 = PI / (180.0 * 3600.0);
+
 /// Minutes per day constant.
 pub static MINUTES_PER_DAY: f64 = 1440.0;
 
+// The gravitational constants below are the WGS-84 values used by default. See
+// `GravityModel` for the alternative constant sets supported by SGP4.
+
 pub static MU: f64 = 398600.5; // in km3 / s2
 
 /// Earth's Radiys (km)
@@ -37,6 +43,98 @@ pub static XKE : f64 = 0.07436685316871385;
 pub static TUMIN : f64 = 13.446851082044981;
 pub static XPDOTP: f64 = 1440.0 / (2.0 * std::f64::consts::PI);
 
+/// Earth gravity constant sets accepted by SGP4 (Vallado's `getgravconst`).
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum GravityModel {
+    /// WGS-72 with the truncated `xke` used by the original AFSPC code.
+    Wgs72Old,
+    /// WGS-72, the set the Vallado verification outputs are generated with.
+    Wgs72,
+    /// WGS-84, the default for this crate.
+    #[default]
+    Wgs84,
+}
+
+/// Constants derived from a `GravityModel`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GravityConstants {
+    /// Minutes in one time unit (1 / xke).
+    pub tumin: f64,
+    /// Earth gravitational parameter, km3 / s2.
+    pub mu: f64,
+    /// Earth equatorial radius, km.
+    pub earth_radius: f64,
+    /// sqrt(mu) in earth radii^1.5 / min.
+    pub xke: f64,
+    pub j2: f64,
+    pub j3: f64,
+    pub j4: f64,
+    pub j3oj2: f64,
+}
+
+impl GravityModel {
+    /// Look up the constants for this model.
+    pub fn constants(self) -> GravityConstants {
+        let (mu, earth_radius, j2, j3, j4) = match self {
+            GravityModel::Wgs72Old => (398600.79964, 6378.135, 0.001082616, -0.00000253881, -0.00000165597),
+            GravityModel::Wgs72 => (398600.8, 6378.135, 0.001082616, -0.00000253881, -0.00000165597),
+            GravityModel::Wgs84 => (MU, EARTH_RADIUS, J2, J3, J4),
+        };
+
+        let xke = match self {
+            GravityModel::Wgs72Old => 0.0743669161,
+            _ => 60.0 / ((earth_radius * earth_radius * earth_radius) / mu).sqrt(),
+        };
+
+        GravityConstants {
+            tumin: 1.0 / xke,
+            mu,
+            earth_radius,
+            xke,
+            j2,
+            j3,
+            j4,
+            j3oj2: j3 / j2,
+        }
+    }
+}
+
+/// Reference ellipsoid of the earth, for geodetic coordinates.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Ellipsoid {
+    /// Equatorial radius (semi-major axis), km.
+    pub a: f64,
+    /// Flattening, (a - b) / a.
+    pub f: f64,
+}
+
+impl Ellipsoid {
+    pub const WGS84: Ellipsoid = Ellipsoid { a: 6378.137, f: 1.0 / 298.257223563 };
+    pub const WGS72: Ellipsoid = Ellipsoid { a: 6378.135, f: 1.0 / 298.26 };
+    pub const GRS80: Ellipsoid = Ellipsoid { a: 6378.137, f: 1.0 / 298.257222101 };
+
+    /// Custom ellipsoid from its equatorial radius in km and flattening.
+    pub fn new(a: f64, f: f64) -> Ellipsoid {
+        Ellipsoid { a, f }
+    }
+
+    /// Polar radius (semi-minor axis), km.
+    pub fn b(&self) -> f64 {
+        self.a * (1.0 - self.f)
+    }
+
+    /// First eccentricity squared.
+    pub fn e2(&self) -> f64 {
+        self.f * (2.0 - self.f)
+    }
+}
+
+impl Default for Ellipsoid {
+    fn default() -> Ellipsoid {
+        Ellipsoid::WGS84
+    }
+}
+
 #[cfg(test)]
 mod test {
   use crate::constants::*;
@@ -68,4 +166,39 @@ mod test {
     assert_eq!(J3OJ2, -0.0023388905587420003);
     assert_eq!(X2O3, 0.6666666666666666);
   }
+
+  #[test]
+  fn test_gravity_models() {
+    let wgs84 = GravityModel::Wgs84.constants();
+    assert_eq!(wgs84.xke, XKE);
+    assert_eq!(wgs84.tumin, TUMIN);
+    assert_eq!(wgs84.j3oj2, J3OJ2);
+    assert_eq!(wgs84.earth_radius, EARTH_RADIUS);
+    assert_eq!(GravityModel::default(), GravityModel::Wgs84);
+
+    let wgs72 = GravityModel::Wgs72.constants();
+    assert_eq!(wgs72.earth_radius, 6378.135);
+    assert_eq!(wgs72.mu, 398600.8);
+    assert_eq!(wgs72.xke, 60.0 / ((6378.135f64 * 6378.135 * 6378.135) / 398600.8).sqrt());
+    assert_eq!(wgs72.j2, 0.001082616);
+    assert_eq!(wgs72.j3, -0.00000253881);
+    assert_eq!(wgs72.j4, -0.00000165597);
+
+    let wgs72old = GravityModel::Wgs72Old.constants();
+    assert_eq!(wgs72old.xke, 0.0743669161);
+    assert_eq!(wgs72old.tumin, 1.0 / 0.0743669161);
+    assert_eq!(wgs72old.mu, 398600.79964);
+    assert_eq!(wgs72old.j2, wgs72.j2);
+  }
+
+  #[test]
+  fn test_ellipsoids() {
+    assert_eq!(Ellipsoid::default(), Ellipsoid::WGS84);
+    assert_eq!(Ellipsoid::WGS84.a, EARTH_RADIUS);
+    crate::tests::assert_diff(Ellipsoid::WGS84.b(), 6356.752314245, 1e-9);
+    crate::tests::assert_diff(Ellipsoid::WGS84.e2(), 0.00669437999014, 1e-14);
+    crate::tests::assert_diff(Ellipsoid::GRS80.b(), 6356.752314140, 1e-9);
+    assert_eq!(Ellipsoid::WGS72.a, GravityModel::Wgs72.constants().earth_radius);
+    assert_eq!(Ellipsoid::new(6378.0, 0.0).b(), 6378.0);
+  }
 }
@@ -1,25 +1,198 @@
+//! Doppler shift of radio links between a ground station and a satellite.
+//!
+//! The shift follows from the signed range rate, found analytically from the satellite's
+//! position and velocity relative to the observer in the earth-fixed frame, where the
+//! station is at rest. Frequencies are shifted to first order in the range rate over the
+//! speed of light.
+
 use crate::constants::*;
-use crate::Vec3;
+use crate::eop::EopTable;
+use crate::frames::{State, Teme};
+use crate::time::Epoch;
+use crate::transforms::{ecf_to_look_angle_rates, teme_to_look_angle_rates};
+use crate::{Ecf, Geodedic, Vec3};
+
+/// Ratio of received to transmitted frequency for a satellite at `position` moving at
+/// `velocity` (km/s) relative to an observer at rest at `location`, all in the same
+/// frame.
+///
+/// The factor is above one while the satellite approaches and below one while it
+/// recedes. For a ground station use earth-fixed coordinates, with the velocity relative
+/// to the rotating earth, or use [`Doppler`].
+pub fn doppler_factor(location: Vec3, position: Vec3, velocity: Vec3) -> f64 {
+    let line_of_sight = position - location;
+    let range_rate = line_of_sight.dot(&velocity) / line_of_sight.norm();
+
+    1.0 - range_rate / C
+}
+
+/// Doppler shift between a ground station and a satellite.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Doppler {
+    /// Range rate in km/s, positive when the satellite recedes.
+    pub range_rate: f64,
+}
+
+impl Doppler {
+    pub fn new(range_rate: f64) -> Doppler {
+        Doppler { range_rate }
+    }
+
+    /// Doppler shift from an ECF position (km) and velocity relative to the rotating earth
+    /// (km/s).
+    pub fn from_ecf(observer: &Geodedic, position: &Ecf, velocity: &Ecf) -> Doppler {
+        let (_, rates) = ecf_to_look_angle_rates(observer, position, velocity);
+        Doppler::new(rates.range)
+    }
+
+    /// Doppler shift from a TEME state, as returned by SGP4, at `epoch`.
+    ///
+    /// The state is converted to ITRF, which takes out the rotation of the earth carrying
+    /// the station along.
+    pub fn from_teme(
+        observer: &Geodedic,
+        state: &State<Teme>,
+        epoch: Epoch,
+        eop: Option<&EopTable>,
+    ) -> Doppler {
+        let (_, rates) = teme_to_look_angle_rates(observer, state, epoch, eop);
+        Doppler::new(rates.range)
+    }
+
+    /// Ratio of received to transmitted frequency, in either direction.
+    pub fn factor(&self) -> f64 {
+        1.0 - self.range_rate / C
+    }
+
+    /// Frequency received on the ground from a satellite transmitting at `nominal`.
+    pub fn downlink(&self, nominal: f64) -> f64 {
+        nominal * self.factor()
+    }
+
+    /// Frequency the ground station transmits for the satellite to receive `nominal`.
+    pub fn uplink(&self, nominal: f64) -> f64 {
+        nominal / self.factor()
+    }
 
-fn sign(val: f64) -> f64 {
-    if val >= 0.0 {
-        1.0
-    } else {
-        -1.0
+    /// Shift of the downlink frequency from `nominal`, negative while receding.
+    pub fn shift(&self, nominal: f64) -> f64 {
+        self.downlink(nominal) - nominal
     }
 }
 
-pub fn doppler_factor(location: Vec3, position: Vec3, velocity: Vec3) -> f64 {
-    let current_range = position.range(&location);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+    use crate::transforms::geodedic_to_ecf;
+
+    fn observer() -> Geodedic {
+        Geodedic {
+            longitude: -122.0308 * DEG_2_RAD,
+            latitude: 36.9613422 * DEG_2_RAD,
+            height: 0.370,
+        }
+    }
+
+    #[test]
+    fn test_signed_factor() {
+        let location = Vec3::new(6378.0, 0.0, 0.0);
+        let position = Vec3::new(7000.0, 0.0, 0.0);
+
+        // approaching raises the frequency, receding lowers it, crossing leaves it
+        let approaching = doppler_factor(location, position, Vec3::new(-7.0, 0.0, 0.0));
+        let receding = doppler_factor(location, position, Vec3::new(7.0, 0.0, 0.0));
+        let crossing = doppler_factor(location, position, Vec3::new(0.0, 7.0, 0.0));
+        assert_diff(approaching, 1.0 + 7.0 / C, 1e-15);
+        assert_diff(receding, 1.0 - 7.0 / C, 1e-15);
+        assert_eq!(crossing, 1.0);
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let doppler = Doppler::new(-5.0);
+        let nominal = 437.5e6;
+
+        // 437.5 MHz approaching at 5 km/s is about 7.3 kHz high
+        assert_diff(doppler.shift(nominal), 7296.7, 0.1);
+        assert!(doppler.downlink(nominal) > nominal);
+        assert!(doppler.uplink(nominal) < nominal);
+
+        // pre-compensated uplink arrives at the nominal frequency
+        assert_diff(doppler.uplink(nominal) * doppler.factor(), nominal, 1e-6);
+        assert_diff(Doppler::new(5.0).shift(nominal), -7296.7, 0.1);
+    }
+
+    #[test]
+    fn test_earth_rotation() {
+        use crate::frames::{FrameVector, EARTH_ROTATION_RATE};
+        use crate::transforms::teme_to_itrf;
+        use chrono::{TimeZone, Utc};
+
+        let observer = observer();
+        let epoch = Epoch::from(Utc.with_ymd_and_hms(2019, 3, 11, 0, 0, 0).unwrap());
 
-    let next_pos = position.add(&velocity);
+        // at rest in inertial space, the satellite moves against the rotating earth
+        let teme = Vec3::new(3000.0, -5000.0, 4500.0);
+        let state = State::<Teme> {
+            position: FrameVector::from_vec3(&teme),
+            velocity: FrameVector::from_vec3(&Vec3::default()),
+        };
+        let doppler = Doppler::from_teme(&observer, &state, epoch, None);
 
-    let next_range = next_pos.range(&location);
+        let itrf = teme_to_itrf(&teme, epoch, None);
+        let velocity = -Vec3::new(0.0, 0.0, EARTH_ROTATION_RATE).cross(&itrf);
+        let expected = doppler_factor(geodedic_to_ecf(&observer), itrf, velocity);
+        assert_diff(doppler.factor(), expected, 1e-15);
+        assert!(doppler.range_rate.abs() > 0.1);
+
+        assert_eq!(Doppler::from_ecf(&observer, &itrf, &velocity), doppler);
+    }
 
-    let mut range_rate = next_range - current_range;
+    #[test]
+    fn test_pass() {
+        use chrono::{Duration, TimeZone, Utc};
 
-    // TODO: is this simply abs()
-    range_rate *= sign(range_rate);
+        let satrec = crate::io::twoline2satrec(
+            "1 25544U 98067A   19070.20068744  .00000619  00000-0  17310-4 0  9990",
+            "2 25544  51.6414 128.3903 0004102  93.2843   5.7821 15.52799004160030",
+        )
+        .unwrap();
+        let observer = observer();
 
-    return 1.0 + (range_rate / C);
+        let start = Utc.with_ymd_and_hms(2019, 3, 11, 0, 0, 0).unwrap();
+        let passes =
+            crate::passes::find_passes(&satrec, &observer, start, start + Duration::days(1), 0.0)
+                .unwrap();
+        let pass = &passes[0];
+
+        let doppler = |tsince: f64| {
+            let time = satrec.epoch() + Duration::nanoseconds((tsince * 60e9) as i64);
+            let epoch = Epoch::from(time);
+            let state = crate::propogation::propogate_datetime(&satrec, epoch)
+                .unwrap()
+                .teme();
+            Doppler::from_teme(&observer, &state, epoch, None)
+        };
+
+        // blue shifted while rising, red shifted while setting, zero near culmination
+        let nominal = 145.8e6;
+        let rising = doppler(pass.rise.tsince);
+        let setting = doppler(pass.set.tsince);
+        assert!(rising.range_rate < -3.0 && rising.shift(nominal) > 1.4e3);
+        assert!(setting.range_rate > 3.0 && setting.shift(nominal) < -1.4e3);
+        assert!(doppler(pass.culmination.tsince).range_rate.abs() < 0.5);
+
+        // the range rate is the change in range, rotation of the earth included
+        let dt = 0.1 / 60.0;
+        let range = |tsince: f64| {
+            let state = crate::propogation::sgp4::sgp4_with_cache(&satrec, tsince, None).unwrap();
+            let time = satrec.epoch() + Duration::nanoseconds((tsince * 60e9) as i64);
+            let itrf = crate::transforms::teme_to_itrf(&state.position, Epoch::from(time), None);
+            (itrf - geodedic_to_ecf(&observer)).norm()
+        };
+        let t = pass.rise.tsince + 2.0;
+        let numeric = (range(t + dt) - range(t - dt)) / 0.2;
+        assert_diff(doppler(t).range_rate, numeric, 1e-4);
+    }
 }
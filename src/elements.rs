@@ -0,0 +1,355 @@
+//! Osculating classical orbital elements, converted from and to position and velocity
+//! following Vallado's `rv2coe` and `coe2rv`.
+
+use crate::anomaly;
+use crate::constants::*;
+use crate::{Mat3, Vec3};
+
+/// Eccentricities and inclinations below this are treated as circular and equatorial.
+const SMALL: f64 = 1e-10;
+
+#[derive(Debug, PartialEq)]
+pub enum ElementsError {
+    /// The position and velocity are parallel (zero angular momentum), so there is no
+    /// orbital plane.
+    Rectilinear,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OrbitType {
+    EllipticalInclined,
+    CircularInclined,
+    EllipticalEquatorial,
+    CircularEquatorial,
+}
+
+/// Classical orbital elements. Angles are in radians, in [0, 2π).
+///
+/// Angles that are undefined for circular or equatorial orbits are zero, and the
+/// remaining ones are measured from the reference that replaces them, as in Vallado:
+///
+/// | orbit                  | `raan` | `argp`                     | `nu`                 |
+/// |------------------------|--------|----------------------------|----------------------|
+/// | elliptical, inclined   | Ω      | ω                          | ν                    |
+/// | circular, inclined     | Ω      | 0                          | argument of latitude |
+/// | elliptical, equatorial | 0      | true longitude of periapsis | ν                    |
+/// | circular, equatorial   | 0      | 0                          | true longitude       |
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct KeplerianElements {
+    /// Semi-latus rectum, km.
+    pub p: f64,
+
+    /// Semi-major axis, km. Negative for hyperbolic orbits, infinite for parabolic ones.
+    pub a: f64,
+
+    /// Eccentricity.
+    pub ecc: f64,
+
+    /// Inclination.
+    pub incl: f64,
+
+    /// Right ascension of the ascending node.
+    pub raan: f64,
+
+    /// Argument of periapsis.
+    pub argp: f64,
+
+    /// True anomaly.
+    pub nu: f64,
+
+    /// Eccentric anomaly; the hyperbolic anomaly for hyperbolic orbits and the parabolic
+    /// anomaly (tan(ν/2)) for parabolic ones.
+    pub eccentric_anomaly: f64,
+
+    /// Mean anomaly.
+    pub mean_anomaly: f64,
+}
+
+fn wrap_two_pi(angle: f64) -> f64 {
+    angle.rem_euclid(TWO_PI)
+}
+
+/// acos of a cosine that rounding may have pushed out of [-1, 1].
+fn safe_acos(cosine: f64) -> f64 {
+    cosine.clamp(-1.0, 1.0).acos()
+}
+
+impl KeplerianElements {
+    pub fn orbit_type(&self) -> OrbitType {
+        let equatorial = self.incl < SMALL || (self.incl - PI).abs() < SMALL;
+
+        match (self.ecc < SMALL, equatorial) {
+            (false, false) => OrbitType::EllipticalInclined,
+            (true, false) => OrbitType::CircularInclined,
+            (false, true) => OrbitType::EllipticalEquatorial,
+            (true, true) => OrbitType::CircularEquatorial,
+        }
+    }
+
+    /// Argument of latitude, ω + ν.
+    pub fn arglat(&self) -> f64 {
+        wrap_two_pi(self.argp + self.nu)
+    }
+
+    /// True longitude of periapsis, Ω + ω.
+    pub fn lonper(&self) -> f64 {
+        wrap_two_pi(self.raan + self.argp)
+    }
+
+    /// True longitude, Ω + ω + ν.
+    pub fn truelon(&self) -> f64 {
+        wrap_two_pi(self.raan + self.argp + self.nu)
+    }
+}
+
+/// Classical elements from a position (km) and velocity (km/s), for a gravitational
+/// parameter `mu` in km³/s².
+pub fn rv2coe(r: &Vec3, v: &Vec3, mu: f64) -> Result<KeplerianElements, ElementsError> {
+    let magr = r.norm();
+    let magv = v.norm();
+
+    let hbar = r.cross(v);
+    let magh = hbar.norm();
+    if magh < SMALL {
+        return Err(ElementsError::Rectilinear);
+    }
+
+    let nbar = Vec3::new(-hbar.y, hbar.x, 0.0);
+
+    let rdotv = r.dot(v);
+    let ebar = ((magv * magv - mu / magr) * *r - rdotv * *v) / mu;
+    let ecc = ebar.norm();
+
+    let sme = magv * magv / 2.0 - mu / magr;
+    let a = if sme.abs() > SMALL {
+        -mu / (2.0 * sme)
+    } else {
+        f64::INFINITY
+    };
+
+    let p = magh * magh / mu;
+    let incl = safe_acos(hbar.z / magh);
+
+    let mut elements = KeplerianElements {
+        p,
+        a,
+        ecc,
+        incl,
+        raan: 0.0,
+        argp: 0.0,
+        nu: 0.0,
+        eccentric_anomaly: 0.0,
+        mean_anomaly: 0.0,
+    };
+
+    let orbit_type = elements.orbit_type();
+    let retrograde = incl > std::f64::consts::FRAC_PI_2;
+
+    // angle from `from` to `to`, taken the long way round when `past_half` is set.
+    let directed = |from: &Vec3, to: &Vec3, past_half: bool| {
+        let angle = from.angle(to);
+        if past_half {
+            TWO_PI - angle
+        } else {
+            angle
+        }
+    };
+
+    match orbit_type {
+        OrbitType::EllipticalInclined | OrbitType::CircularInclined => {
+            elements.raan = directed(&Vec3::new(1.0, 0.0, 0.0), &nbar, nbar.y < 0.0);
+
+            if orbit_type == OrbitType::EllipticalInclined {
+                elements.argp = directed(&nbar, &ebar, ebar.z < 0.0);
+                elements.nu = directed(&ebar, r, rdotv < 0.0);
+            } else {
+                elements.nu = directed(&nbar, r, r.z < 0.0);
+            }
+        }
+        OrbitType::EllipticalEquatorial => {
+            let lonper = directed(&Vec3::new(1.0, 0.0, 0.0), &ebar, ebar.y < 0.0);
+            elements.argp = if retrograde { TWO_PI - lonper } else { lonper };
+            elements.nu = directed(&ebar, r, rdotv < 0.0);
+        }
+        OrbitType::CircularEquatorial => {
+            let truelon = safe_acos(r.x / magr);
+            let truelon = if r.y < 0.0 { TWO_PI - truelon } else { truelon };
+            elements.nu = if retrograde {
+                TWO_PI - truelon
+            } else {
+                truelon
+            };
+        }
+    }
+
+    elements.argp = wrap_two_pi(elements.argp);
+    elements.nu = wrap_two_pi(elements.nu);
+
+    // the true anomaly of an actual state lies within the asymptotes of an open orbit.
+    let signed_nu = if elements.nu > PI {
+        elements.nu - TWO_PI
+    } else {
+        elements.nu
+    };
+    let eccentric_anomaly = anomaly::true_to_eccentric(signed_nu, ecc).unwrap_or(f64::NAN);
+    let mean_anomaly = anomaly::eccentric_to_mean(eccentric_anomaly, ecc).unwrap_or(f64::NAN);
+
+    if ecc < 1.0 {
+        elements.eccentric_anomaly = wrap_two_pi(eccentric_anomaly);
+        elements.mean_anomaly = wrap_two_pi(mean_anomaly);
+    } else {
+        elements.eccentric_anomaly = eccentric_anomaly;
+        elements.mean_anomaly = mean_anomaly;
+    }
+
+    Ok(elements)
+}
+
+/// Position (km) and velocity (km/s) from classical elements, for a gravitational
+/// parameter `mu` in km³/s². Only `p`, `ecc`, `incl`, `raan`, `argp` and `nu` are used.
+pub fn coe2rv(elements: &KeplerianElements, mu: f64) -> (Vec3, Vec3) {
+    let (sinnu, cosnu) = elements.nu.sin_cos();
+    let radius = elements.p / (1.0 + elements.ecc * cosnu);
+
+    // Vallado guards against a zero semi-latus rectum.
+    let p = if elements.p.abs() < 0.0001 {
+        0.0001
+    } else {
+        elements.p
+    };
+    let speed = (mu / p).sqrt();
+
+    let rpqw = Vec3::new(radius * cosnu, radius * sinnu, 0.0);
+    let vpqw = Vec3::new(-sinnu * speed, (elements.ecc + cosnu) * speed, 0.0);
+
+    let pqw_to_ijk = Mat3::rotation_z(-elements.raan)
+        * Mat3::rotation_x(-elements.incl)
+        * Mat3::rotation_z(-elements.argp);
+
+    (pqw_to_ijk * rpqw, pqw_to_ijk * vpqw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+
+    const MU_EGM96: f64 = 398600.4418;
+
+    #[test]
+    fn test_rv2coe() {
+        // Vallado, Fundamentals of Astrodynamics and Applications, example 2-5.
+        let r = Vec3::new(6524.834, 6862.875, 6448.296);
+        let v = Vec3::new(4.901327, 5.533756, -1.976341);
+        let elements = rv2coe(&r, &v, MU_EGM96).unwrap();
+
+        assert_eq!(elements.orbit_type(), OrbitType::EllipticalInclined);
+        assert_diff(elements.p, 11067.790, 1e-2);
+        assert_diff(elements.a, 36127.343, 1e-2);
+        assert_diff(elements.ecc, 0.832853, 1e-6);
+        assert_diff(elements.incl * RAD_TO_DEG, 87.870, 1e-3);
+        assert_diff(elements.raan * RAD_TO_DEG, 227.898, 1e-3);
+        assert_diff(elements.argp * RAD_TO_DEG, 53.38, 1e-2);
+        assert_diff(elements.nu * RAD_TO_DEG, 92.335, 1e-3);
+        assert_diff(elements.arglat(), elements.argp + elements.nu, 1e-12);
+        assert_diff(elements.lonper(), elements.raan + elements.argp, 1e-12);
+
+        let e = elements.eccentric_anomaly;
+        assert_diff(elements.mean_anomaly, e - elements.ecc * e.sin(), 1e-12);
+    }
+
+    #[test]
+    fn test_coe2rv() {
+        // Vallado example 2-6.
+        let elements = KeplerianElements {
+            p: 11067.790,
+            a: 36127.343,
+            ecc: 0.83285,
+            incl: 87.87 * DEG_2_RAD,
+            raan: 227.89 * DEG_2_RAD,
+            argp: 53.38 * DEG_2_RAD,
+            nu: 92.335 * DEG_2_RAD,
+            eccentric_anomaly: 0.0,
+            mean_anomaly: 0.0,
+        };
+
+        let (r, v) = coe2rv(&elements, MU_EGM96);
+        assert_diff(r.x, 6525.368, 1e-3);
+        assert_diff(r.y, 6861.532, 1e-3);
+        assert_diff(r.z, 6449.119, 1e-3);
+        assert_diff(v.x, 4.902279, 1e-6);
+        assert_diff(v.y, 5.533140, 1e-6);
+        assert_diff(v.z, -1.975710, 1e-6);
+    }
+
+    #[test]
+    fn test_special_cases() {
+        let cases = [
+            (0.3, 0.9, OrbitType::EllipticalInclined),
+            (0.0, 0.9, OrbitType::CircularInclined),
+            (0.3, 0.0, OrbitType::EllipticalEquatorial),
+            (0.0, 0.0, OrbitType::CircularEquatorial),
+            (0.3, PI, OrbitType::EllipticalEquatorial),
+            (0.0, PI, OrbitType::CircularEquatorial),
+            (1.0, 0.5, OrbitType::EllipticalInclined),
+            (2.5, 0.5, OrbitType::EllipticalInclined),
+        ];
+
+        for &(ecc, incl, orbit_type) in &cases {
+            for &nu in &[0.0, 0.4, 2.0, 4.5] {
+                // hyperbolic and parabolic orbits only reach part of the circle.
+                if ecc >= 1.0 && (1.0 + ecc * f64::cos(nu)) < 0.2 {
+                    continue;
+                }
+
+                let elements = KeplerianElements {
+                    p: 7000.0 * (1.0 - ecc * ecc).abs().max(0.5),
+                    a: 0.0,
+                    ecc,
+                    incl,
+                    raan: if incl == 0.0 || incl == PI { 0.0 } else { 1.2 },
+                    argp: if ecc == 0.0 { 0.0 } else { 5.1 },
+                    nu,
+                    eccentric_anomaly: 0.0,
+                    mean_anomaly: 0.0,
+                };
+
+                let (r, v) = coe2rv(&elements, MU_EGM96);
+                let back = rv2coe(&r, &v, MU_EGM96).unwrap();
+
+                assert_eq!(back.orbit_type(), orbit_type, "{} {} {}", ecc, incl, nu);
+                assert_diff(back.p, elements.p, 1e-7);
+                assert_diff(back.ecc, ecc, 1e-12);
+                assert_diff(back.incl, incl, 1e-12);
+                assert_diff(back.truelon(), elements.truelon(), 1e-10);
+
+                let (r2, v2) = coe2rv(&back, MU_EGM96);
+                assert!(r2.range(&r) < 1e-7);
+                assert!(v2.range(&v) < 1e-10);
+            }
+        }
+
+        let r = Vec3::new(7000.0, 0.0, 0.0);
+        assert_eq!(
+            rv2coe(&r, &(r * 1e-3), MU_EGM96),
+            Err(ElementsError::Rectilinear)
+        );
+    }
+
+    #[test]
+    fn test_sgp4_state() {
+        let satrec = crate::io::twoline2satrec(
+            "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+            "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667",
+        )
+        .unwrap();
+
+        let state = crate::propogation::sgp4::sgp4_with_cache(&satrec, 0.0, None).unwrap();
+        let elements = rv2coe(&state.position, &state.velocity, MU).unwrap();
+
+        // osculating elements stay close to the mean elements of the TLE.
+        assert_diff(elements.incl * RAD_TO_DEG, 34.2682, 0.1);
+        assert_diff(elements.raan * RAD_TO_DEG, 348.7242, 0.1);
+        assert_diff(elements.ecc, 0.1859667, 1e-3);
+    }
+}
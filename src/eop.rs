@@ -0,0 +1,377 @@
+//! Earth orientation parameters from IERS data files.
+//!
+//! Reads the IERS rapid service `finals2000A.all` / `finals.all` files and the `EOPC04`
+//! series, and interpolates their daily values. The resulting [`EopTable`] supplies
+//! UT1 - UTC to [`gstime_eop`](crate::propogation::gstime::gstime_eop) and polar motion
+//! to [`teme_to_itrf`](crate::transforms::teme_to_itrf).
+
+use crate::time::{Epoch, TimeScale};
+
+#[derive(Debug, PartialEq)]
+pub enum EopError {
+    /// A data line could not be read (line number, line).
+    InvalidLine(usize, String),
+    /// The file has no EOP entries.
+    Empty,
+    /// The file could not be read.
+    Io(String),
+}
+
+/// Earth orientation parameters at one time.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct EopEntry {
+    /// Modified julian date (UTC).
+    pub mjd: f64,
+
+    /// Polar motion x, in arcseconds.
+    pub xp: f64,
+
+    /// Polar motion y, in arcseconds.
+    pub yp: f64,
+
+    /// UT1 - UTC, in seconds.
+    pub dut1: f64,
+
+    /// Excess length of day, in seconds.
+    pub lod: f64,
+
+    /// Celestial pole offset, in arcseconds: dX, or dψ in an IAU 1980 table (see
+    /// [`NutationSeries`]).
+    pub dx: f64,
+
+    /// Celestial pole offset, in arcseconds: dY, or dε in an IAU 1980 table.
+    pub dy: f64,
+}
+
+/// The nutation theory the celestial pole offsets of an EOP file refer to.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NutationSeries {
+    /// dX and dY for the IAU 2000/2006 models, as in `finals2000A.all` and the EOPC04
+    /// 14 and 20 series.
+    Iau2000,
+    /// dψ and dε corrections to IAU 1980 nutation, as in `finals.all` and the IAU 1980
+    /// EOPC04 series.
+    Iau1980,
+}
+
+/// Daily earth orientation parameters, interpolated linearly between entries.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EopTable {
+    /// Entries in increasing order of date.
+    entries: Vec<EopEntry>,
+
+    /// What `dx` and `dy` of the entries hold.
+    series: NutationSeries,
+}
+
+/// Parse a fixed-width field of a `finals` line, `None` when it is blank or missing.
+fn finals_field(line: &str, start: usize, end: usize) -> Option<Result<f64, ()>> {
+    let field = line.get(start..end.min(line.len()))?.trim();
+    if field.is_empty() {
+        return None;
+    }
+    Some(field.parse::<f64>().map_err(|_| ()))
+}
+
+/// Read a line of the `finals` format, see `readme.finals2000A` for the columns.
+///
+/// Bulletin A values are used. Predictions without nutation have zero pole offsets.
+fn parse_finals(line: &str) -> Option<EopEntry> {
+    let required = |start, end| finals_field(line, start, end)?.ok();
+    let optional = |start, end| match finals_field(line, start, end) {
+        Some(value) => value.ok(),
+        None => Some(0.0),
+    };
+
+    Some(EopEntry {
+        mjd: required(7, 15)?,
+        xp: required(18, 27)?,
+        yp: required(37, 46)?,
+        dut1: required(58, 68)?,
+        lod: optional(79, 86)? / 1e3,
+        dx: optional(97, 106)? / 1e3,
+        dy: optional(116, 125)? / 1e3,
+    })
+}
+
+/// Read a line of the `EOPC04` formats, which are separated by whitespace.
+///
+/// The 14 series has `year month day MJD x y UT1-UTC LOD dX dY ...`, the 20 series adds
+/// the hour before the MJD and moves LOD after the pole rates.
+fn parse_c04(fields: &[&str]) -> Option<EopEntry> {
+    let values = fields
+        .iter()
+        .map(|field| field.parse::<f64>().ok())
+        .collect::<Option<Vec<f64>>>()?;
+
+    // the 20 series has an hour of day where the 14 series has the MJD.
+    if values.len() >= 13 && values[3] < 24.0 {
+        Some(EopEntry {
+            mjd: values[4] + values[3] / 24.0,
+            xp: values[5],
+            yp: values[6],
+            dut1: values[7],
+            dx: values[8],
+            dy: values[9],
+            lod: values[12],
+        })
+    } else if values.len() >= 10 {
+        Some(EopEntry {
+            mjd: values[3],
+            xp: values[4],
+            yp: values[5],
+            dut1: values[6],
+            lod: values[7],
+            dx: values[8],
+            dy: values[9],
+        })
+    } else {
+        None
+    }
+}
+
+/// Whether a line is a `finals` data line, which has an IERS (I) or prediction (P) flag
+/// for polar motion in column 17.
+fn is_finals_line(line: &str) -> bool {
+    matches!(line.as_bytes().get(16), Some(b'I') | Some(b'P'))
+}
+
+impl EopTable {
+    /// Table from a list of entries, which are sorted by date. Their pole offsets are
+    /// taken as IAU 2000 dX and dY, see `with_series`.
+    pub fn new(mut entries: Vec<EopEntry>) -> Result<EopTable, EopError> {
+        if entries.is_empty() {
+            return Err(EopError::Empty);
+        }
+
+        entries.sort_by(|a, b| a.mjd.total_cmp(&b.mjd));
+        Ok(EopTable {
+            entries,
+            series: NutationSeries::Iau2000,
+        })
+    }
+
+    /// Parse an IERS EOP file.
+    ///
+    /// Accepts `finals2000A.all` (and the other `finals` files of the same layout) and
+    /// `EOPC04` files of the 14 and 20 series. Header and comment lines, and dates in
+    /// `finals` files that have no values yet, are skipped.
+    ///
+    /// The file layouts do not say which nutation theory the pole offsets refer to. They
+    /// are taken as IAU 2000 dX and dY; use `with_series` for `finals.all` and other
+    /// IAU 1980 files.
+    pub fn parse(text: &str) -> Result<EopTable, EopError> {
+        let mut entries = vec![];
+
+        for (number, line) in text.lines().enumerate() {
+            let entry = if is_finals_line(line) {
+                parse_finals(line)
+            } else {
+                let fields = line.split_whitespace().collect::<Vec<&str>>();
+
+                // headers, and dates beyond the end of the predictions.
+                if fields
+                    .first()
+                    .map_or(true, |field| field.parse::<i32>().is_err())
+                    || fields.len() <= 4
+                {
+                    continue;
+                }
+
+                parse_c04(&fields)
+            };
+
+            match entry {
+                Some(entry) => entries.push(entry),
+                None => return Err(EopError::InvalidLine(number + 1, line.to_string())),
+            }
+        }
+
+        EopTable::new(entries)
+    }
+
+    /// Read and parse an IERS EOP file from disk.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<EopTable, EopError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => EopTable::parse(&text),
+            Err(err) => Err(EopError::Io(err.to_string())),
+        }
+    }
+
+    /// The same table with its pole offsets referring to `series`.
+    pub fn with_series(self, series: NutationSeries) -> EopTable {
+        EopTable { series, ..self }
+    }
+
+    pub fn entries(&self) -> &[EopEntry] {
+        &self.entries
+    }
+
+    pub fn series(&self) -> NutationSeries {
+        self.series
+    }
+
+    /// Parameters at a modified julian date (UTC), or `None` outside the table.
+    ///
+    /// A leap second between two entries is removed from UT1 - UTC before
+    /// interpolating, so the value only jumps at the leap second itself.
+    pub fn interpolate(&self, mjd: f64) -> Option<EopEntry> {
+        let first = self.entries.first()?;
+        let last = self.entries.last()?;
+        if mjd < first.mjd || mjd > last.mjd {
+            return None;
+        }
+
+        let after = self.entries.partition_point(|entry| entry.mjd <= mjd);
+        if after >= self.entries.len() {
+            return Some(EopEntry { mjd, ..*last });
+        }
+
+        let a = &self.entries[after - 1];
+        let b = &self.entries[after];
+        let t = (mjd - a.mjd) / (b.mjd - a.mjd);
+        let lerp = |from: f64, to: f64| from + (to - from) * t;
+
+        let leap = (b.dut1 - a.dut1).round();
+
+        Some(EopEntry {
+            mjd,
+            xp: lerp(a.xp, b.xp),
+            yp: lerp(a.yp, b.yp),
+            dut1: lerp(a.dut1, b.dut1 - leap),
+            lod: lerp(a.lod, b.lod),
+            dx: lerp(a.dx, b.dx),
+            dy: lerp(a.dy, b.dy),
+        })
+    }
+
+    /// Parameters at an epoch on any time scale, or `None` outside the table.
+    pub fn at(&self, epoch: Epoch) -> Option<EopEntry> {
+        self.interpolate(epoch.to_scale(TimeScale::Utc).mjd())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+
+    const FINALS: &str = "\
+04 4 6 53101.00 I -0.140682 0.000060  0.333309 0.000069  I-0.4399619 0.0000069  1.5563 0.0043  I    -0.205    0.152    -0.241    0.160 -0.140682  0.333309 -0.4399619    -0.205    -0.241
+04 4 7 53102.00 I -0.139287 0.000060  0.333020 0.000069  I-0.4414849 0.0000069  1.4651 0.0043  I    -0.212    0.152    -0.236    0.160
+04 4 8 53103.00 P -0.137881 0.000060  0.332719 0.000069  P-0.4429101 0.0000069
+04 4 9 53104.00
+";
+
+    #[test]
+    fn test_finals() {
+        let table = EopTable::parse(FINALS).unwrap();
+        let entries = table.entries();
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(
+            entries[0],
+            EopEntry {
+                mjd: 53101.0,
+                xp: -0.140682,
+                yp: 0.333309,
+                dut1: -0.4399619,
+                lod: 0.0015563,
+                dx: -0.000205,
+                dy: -0.000241,
+            }
+        );
+
+        // predictions without LOD or nutation.
+        assert_eq!(entries[2].lod, 0.0);
+        assert_eq!(entries[2].dx, 0.0);
+        assert_eq!(entries[2].dut1, -0.4429101);
+    }
+
+    #[test]
+    fn test_c04() {
+        let c04_14 = "\
+                           EARTH ORIENTATION PARAMETER (EOP) PRODUCT CENTER CENTER (PARIS OBSERVATORY)
+      Date      MJD      x          y        UT1-UTC       LOD         dX        dY        x Err     y Err   UT1-UTC Err  LOD Err     dX Err       dY Err
+                         \"          \"           s           s          \"         \"           \"          \"          s         s            \"           \"
+   (0h UTC)
+
+2004   4   6  53101  -0.140650   0.333271  -0.4399615   0.0015590  -0.000209  -0.000245   0.000030   0.000030  0.0000080  0.0000100    0.000040    0.000040
+2004   4   7  53102  -0.139254   0.332980  -0.4414793   0.0014712  -0.000211  -0.000240   0.000030   0.000030  0.0000080  0.0000100    0.000040    0.000040
+";
+        let c04_20 = "\
+# YR  MM  DD  HH       MJD        x(\")        y(\")  UT1-UTC(s)       dX(\")      dY(\")       xrt(\")      yrt(\")      LOD(s)
+2004   4   6   0  53101.00   -0.140650    0.333271  -0.4399615   -0.000209   -0.000245    0.001395   -0.000290   0.0015590
+2004   4   7   0  53102.00   -0.139254    0.332980  -0.4414793   -0.000211   -0.000240    0.001401   -0.000302   0.0014712
+";
+
+        let expected = EopEntry {
+            mjd: 53101.0,
+            xp: -0.140650,
+            yp: 0.333271,
+            dut1: -0.4399615,
+            lod: 0.0015590,
+            dx: -0.000209,
+            dy: -0.000245,
+        };
+
+        for text in [c04_14, c04_20] {
+            let table = EopTable::parse(text).unwrap();
+            assert_eq!(table.entries().len(), 2);
+            assert_eq!(table.entries()[0], expected);
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let table = EopTable::parse(FINALS).unwrap();
+
+        let noon = table.interpolate(53101.5).unwrap();
+        assert_diff(noon.xp, (-0.140682 + -0.139287) / 2.0, 1e-15);
+        assert_diff(noon.dut1, (-0.4399619 + -0.4414849) / 2.0, 1e-15);
+        assert_eq!(table.interpolate(53103.0).unwrap().dut1, -0.4429101);
+        assert_eq!(table.interpolate(53100.9), None);
+        assert_eq!(table.interpolate(53103.1), None);
+
+        let epoch = Epoch::from_jd(2453101.5 + 0.25, TimeScale::Utc);
+        let tai = epoch.to_scale(TimeScale::Tai);
+        assert_eq!(table.at(tai), table.at(epoch));
+        assert_diff(table.at(epoch).unwrap().mjd, 53101.25, 1e-9);
+    }
+
+    #[test]
+    fn test_leap_second() {
+        // UT1 - UTC jumps by a second at the start of 2017.
+        let table = EopTable::new(vec![
+            EopEntry {
+                mjd: 57753.0,
+                dut1: -0.4076,
+                ..EopEntry::default()
+            },
+            EopEntry {
+                mjd: 57754.0,
+                dut1: 0.5921,
+                ..EopEntry::default()
+            },
+        ])
+        .unwrap();
+
+        assert_diff(table.interpolate(57753.5).unwrap().dut1, -0.40775, 1e-12);
+        assert_eq!(table.interpolate(57754.0).unwrap().dut1, 0.5921);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(EopTable::parse(""), Err(EopError::Empty));
+        assert_eq!(
+            EopTable::parse(
+                "2004   4   6  53101  -0.140650   x   -0.4399615   0.0015590  -0.000209  -0.000245"
+            ),
+            Err(EopError::InvalidLine(
+                1,
+                "2004   4   6  53101  -0.140650   x   -0.4399615   0.0015590  -0.000209  -0.000245"
+                    .to_string()
+            ))
+        );
+    }
+}
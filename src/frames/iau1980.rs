@@ -0,0 +1,231 @@
+//! IAU-1976 precession and IAU-1980 nutation, the FK5 reduction used by Vallado.
+//!
+//! All functions take `ttt`, julian centuries of TT since J2000, and return radians.
+
+use crate::constants::*;
+
+/// Reduce an angle to [-π, π).
+fn normalize(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(TWO_PI) - PI
+}
+
+/// Mean obliquity of the ecliptic (IAU 1980).
+pub fn mean_obliquity(ttt: f64) -> f64 {
+    (84381.448 + (-46.8150 + (-0.00059 + 0.001813 * ttt) * ttt) * ttt) * ARCSEC_TO_RAD
+}
+
+/// Precession angles ζ, θ and z from J2000 to the mean equator and equinox of date
+/// (IAU 1976).
+pub fn precession_angles(ttt: f64) -> (f64, f64, f64) {
+    let zeta = (2306.2181 + (0.30188 + 0.017998 * ttt) * ttt) * ttt;
+    let theta = (2004.3109 + (-0.42665 - 0.041833 * ttt) * ttt) * ttt;
+    let z = (2306.2181 + (1.09468 + 0.018203 * ttt) * ttt) * ttt;
+
+    (
+        zeta * ARCSEC_TO_RAD,
+        theta * ARCSEC_TO_RAD,
+        z * ARCSEC_TO_RAD,
+    )
+}
+
+/// Delaunay arguments l, l', F, D and Ω of the IAU 1980 nutation theory.
+fn fundamental_arguments(ttt: f64) -> [f64; 5] {
+    // arcseconds, plus whole revolutions per century.
+    let argument = |a0: f64, a1: f64, a2: f64, a3: f64, revolutions: f64| {
+        normalize(
+            (a0 + (a1 + (a2 + a3 * ttt) * ttt) * ttt) * ARCSEC_TO_RAD
+                + (revolutions * ttt % 1.0) * TWO_PI,
+        )
+    };
+
+    [
+        argument(485866.733, 715922.633, 31.310, 0.064, 1325.0),
+        argument(1287099.804, 1292581.224, -0.577, -0.012, 99.0),
+        argument(335778.877, 295263.137, -13.257, 0.011, 1342.0),
+        argument(1072261.307, 1105601.328, -6.891, 0.019, 1236.0),
+        argument(450160.280, -482890.539, 7.455, 0.008, -5.0),
+    ]
+}
+
+/// Nutation in longitude Δψ and in obliquity Δε (IAU 1980, all 106 terms).
+pub fn nutation(ttt: f64) -> (f64, f64) {
+    let arguments = fundamental_arguments(ttt);
+
+    let mut dpsi = 0.0;
+    let mut deps = 0.0;
+
+    // smallest terms first, to limit rounding.
+    for (multipliers, [sp, spt, ce, cet]) in NUTATION_TERMS.iter().rev() {
+        let angle = multipliers
+            .iter()
+            .zip(arguments.iter())
+            .map(|(&n, &argument)| n as f64 * argument)
+            .sum::<f64>();
+
+        dpsi += (sp + spt * ttt) * angle.sin();
+        deps += (ce + cet * ttt) * angle.cos();
+    }
+
+    // coefficients are in units of 0.1 mas.
+    (dpsi * 1e-4 * ARCSEC_TO_RAD, deps * 1e-4 * ARCSEC_TO_RAD)
+}
+
+/// Equation of the equinoxes, GAST - GMST, from the nutation in longitude and the mean
+/// obliquity.
+///
+/// The kinematic terms in Ω are included from 1997-02-27, when the IAU adopted them.
+pub fn equation_of_equinoxes(ttt: f64, dpsi: f64, mean_eps: f64) -> f64 {
+    let geometric = dpsi * mean_eps.cos();
+
+    if ttt * 36525.0 + 2451545.0 > 2450449.5 {
+        let omega = fundamental_arguments(ttt)[4];
+        geometric + (0.00264 * omega.sin() + 0.000063 * (2.0 * omega).sin()) * ARCSEC_TO_RAD
+    } else {
+        geometric
+    }
+}
+
+/// Multipliers of l, l', F, D, Ω and the coefficients of sin (Δψ) and cos (Δε), with
+/// their rates per century, in units of 0.1 mas.
+#[rustfmt::skip]
+const NUTATION_TERMS: [([i8; 5], [f64; 4]); 106] = [
+    // 1-10
+    ([ 0,  0,  0,  0,  1], [-171996.0, -174.2, 92025.0,  8.9]),
+    ([ 0,  0,  0,  0,  2], [   2062.0,    0.2,  -895.0,  0.5]),
+    ([-2,  0,  2,  0,  1], [     46.0,    0.0,   -24.0,  0.0]),
+    ([ 2,  0, -2,  0,  0], [     11.0,    0.0,     0.0,  0.0]),
+    ([-2,  0,  2,  0,  2], [     -3.0,    0.0,     1.0,  0.0]),
+    ([ 1, -1,  0, -1,  0], [     -3.0,    0.0,     0.0,  0.0]),
+    ([ 0, -2,  2, -2,  1], [     -2.0,    0.0,     1.0,  0.0]),
+    ([ 2,  0, -2,  0,  1], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0,  2, -2,  2], [ -13187.0,   -1.6,  5736.0, -3.1]),
+    ([ 0,  1,  0,  0,  0], [   1426.0,   -3.4,    54.0, -0.1]),
+    // 11-20
+    ([ 0,  1,  2, -2,  2], [   -517.0,    1.2,   224.0, -0.6]),
+    ([ 0, -1,  2, -2,  2], [    217.0,   -0.5,   -95.0,  0.3]),
+    ([ 0,  0,  2, -2,  1], [    129.0,    0.1,   -70.0,  0.0]),
+    ([ 2,  0,  0, -2,  0], [     48.0,    0.0,     1.0,  0.0]),
+    ([ 0,  0,  2, -2,  0], [    -22.0,    0.0,     0.0,  0.0]),
+    ([ 0,  2,  0,  0,  0], [     17.0,   -0.1,     0.0,  0.0]),
+    ([ 0,  1,  0,  0,  1], [    -15.0,    0.0,     9.0,  0.0]),
+    ([ 0,  2,  2, -2,  2], [    -16.0,    0.1,     7.0,  0.0]),
+    ([ 0, -1,  0,  0,  1], [    -12.0,    0.0,     6.0,  0.0]),
+    ([-2,  0,  0,  2,  1], [     -6.0,    0.0,     3.0,  0.0]),
+    // 21-30
+    ([ 0, -1,  2, -2,  1], [     -5.0,    0.0,     3.0,  0.0]),
+    ([ 2,  0,  0, -2,  1], [      4.0,    0.0,    -2.0,  0.0]),
+    ([ 0,  1,  2, -2,  1], [      4.0,    0.0,    -2.0,  0.0]),
+    ([ 1,  0,  0, -1,  0], [     -4.0,    0.0,     0.0,  0.0]),
+    ([ 2,  1,  0, -2,  0], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0, -2,  2,  1], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  1, -2,  2,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  1,  0,  0,  2], [      1.0,    0.0,     0.0,  0.0]),
+    ([-1,  0,  0,  1,  1], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  1,  2, -2,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    // 31-40
+    ([ 0,  0,  2,  0,  2], [  -2274.0,   -0.2,   977.0, -0.5]),
+    ([ 1,  0,  0,  0,  0], [    712.0,    0.1,    -7.0,  0.0]),
+    ([ 0,  0,  2,  0,  1], [   -386.0,   -0.4,   200.0,  0.0]),
+    ([ 1,  0,  2,  0,  2], [   -301.0,    0.0,   129.0, -0.1]),
+    ([ 1,  0,  0, -2,  0], [   -158.0,    0.0,    -1.0,  0.0]),
+    ([-1,  0,  2,  0,  2], [    123.0,    0.0,   -53.0,  0.0]),
+    ([ 0,  0,  0,  2,  0], [     63.0,    0.0,    -2.0,  0.0]),
+    ([ 1,  0,  0,  0,  1], [     63.0,    0.1,   -33.0,  0.0]),
+    ([-1,  0,  0,  0,  1], [    -58.0,   -0.1,    32.0,  0.0]),
+    ([-1,  0,  2,  2,  2], [    -59.0,    0.0,    26.0,  0.0]),
+    // 41-50
+    ([ 1,  0,  2,  0,  1], [    -51.0,    0.0,    27.0,  0.0]),
+    ([ 0,  0,  2,  2,  2], [    -38.0,    0.0,    16.0,  0.0]),
+    ([ 2,  0,  0,  0,  0], [     29.0,    0.0,    -1.0,  0.0]),
+    ([ 1,  0,  2, -2,  2], [     29.0,    0.0,   -12.0,  0.0]),
+    ([ 2,  0,  2,  0,  2], [    -31.0,    0.0,    13.0,  0.0]),
+    ([ 0,  0,  2,  0,  0], [     26.0,    0.0,    -1.0,  0.0]),
+    ([-1,  0,  2,  0,  1], [     21.0,    0.0,   -10.0,  0.0]),
+    ([-1,  0,  0,  2,  1], [     16.0,    0.0,    -8.0,  0.0]),
+    ([ 1,  0,  0, -2,  1], [    -13.0,    0.0,     7.0,  0.0]),
+    ([-1,  0,  2,  2,  1], [    -10.0,    0.0,     5.0,  0.0]),
+    // 51-60
+    ([ 1,  1,  0, -2,  0], [     -7.0,    0.0,     0.0,  0.0]),
+    ([ 0,  1,  2,  0,  2], [      7.0,    0.0,    -3.0,  0.0]),
+    ([ 0, -1,  2,  0,  2], [     -7.0,    0.0,     3.0,  0.0]),
+    ([ 1,  0,  2,  2,  2], [     -8.0,    0.0,     3.0,  0.0]),
+    ([ 1,  0,  0,  2,  0], [      6.0,    0.0,     0.0,  0.0]),
+    ([ 2,  0,  2, -2,  2], [      6.0,    0.0,    -3.0,  0.0]),
+    ([ 0,  0,  0,  2,  1], [     -6.0,    0.0,     3.0,  0.0]),
+    ([ 0,  0,  2,  2,  1], [     -7.0,    0.0,     3.0,  0.0]),
+    ([ 1,  0,  2, -2,  1], [      6.0,    0.0,    -3.0,  0.0]),
+    ([ 0,  0,  0, -2,  1], [     -5.0,    0.0,     3.0,  0.0]),
+    // 61-70
+    ([ 1, -1,  0,  0,  0], [      5.0,    0.0,     0.0,  0.0]),
+    ([ 2,  0,  2,  0,  1], [     -5.0,    0.0,     3.0,  0.0]),
+    ([ 0,  1,  0, -2,  0], [     -4.0,    0.0,     0.0,  0.0]),
+    ([ 1,  0, -2,  0,  0], [      4.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0,  0,  1,  0], [     -4.0,    0.0,     0.0,  0.0]),
+    ([ 1,  1,  0,  0,  0], [     -3.0,    0.0,     0.0,  0.0]),
+    ([ 1,  0,  2,  0,  0], [      3.0,    0.0,     0.0,  0.0]),
+    ([ 1, -1,  2,  0,  2], [     -3.0,    0.0,     1.0,  0.0]),
+    ([-1, -1,  2,  2,  2], [     -3.0,    0.0,     1.0,  0.0]),
+    ([-2,  0,  0,  0,  1], [     -2.0,    0.0,     1.0,  0.0]),
+    // 71-80
+    ([ 3,  0,  2,  0,  2], [     -3.0,    0.0,     1.0,  0.0]),
+    ([ 0, -1,  2,  2,  2], [     -3.0,    0.0,     1.0,  0.0]),
+    ([ 1,  1,  2,  0,  2], [      2.0,    0.0,    -1.0,  0.0]),
+    ([-1,  0,  2, -2,  1], [     -2.0,    0.0,     1.0,  0.0]),
+    ([ 2,  0,  0,  0,  1], [      2.0,    0.0,    -1.0,  0.0]),
+    ([ 1,  0,  0,  0,  2], [     -2.0,    0.0,     1.0,  0.0]),
+    ([ 3,  0,  0,  0,  0], [      2.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0,  2,  1,  2], [      2.0,    0.0,    -1.0,  0.0]),
+    ([-1,  0,  0,  0,  2], [      1.0,    0.0,    -1.0,  0.0]),
+    ([ 1,  0,  0, -4,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    // 81-90
+    ([-2,  0,  2,  2,  2], [      1.0,    0.0,    -1.0,  0.0]),
+    ([-1,  0,  2,  4,  2], [     -2.0,    0.0,     1.0,  0.0]),
+    ([ 2,  0,  0, -4,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 1,  1,  2, -2,  2], [      1.0,    0.0,    -1.0,  0.0]),
+    ([ 1,  0,  2,  2,  1], [     -1.0,    0.0,     1.0,  0.0]),
+    ([-2,  0,  2,  4,  2], [     -1.0,    0.0,     1.0,  0.0]),
+    ([-1,  0,  4,  0,  2], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 1, -1,  0, -2,  0], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 2,  0,  2, -2,  1], [      1.0,    0.0,    -1.0,  0.0]),
+    ([ 2,  0,  2,  2,  2], [     -1.0,    0.0,     0.0,  0.0]),
+    // 91-100
+    ([ 1,  0,  0,  2,  1], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0,  4, -2,  2], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 3,  0,  2, -2,  2], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 1,  0,  2, -2,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  1,  2,  0,  1], [      1.0,    0.0,     0.0,  0.0]),
+    ([-1, -1,  0,  2,  1], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0, -2,  0,  1], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0,  2, -1,  2], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  1,  0,  2,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 1,  0, -2, -2,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    // 101-106
+    ([ 0, -1,  2,  0,  1], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 1,  1,  0, -2,  1], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 1,  0, -2,  2,  0], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 2,  0,  0,  2,  0], [      1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  0,  2,  4,  2], [     -1.0,    0.0,     0.0,  0.0]),
+    ([ 0,  1,  0,  1,  0], [      1.0,    0.0,     0.0,  0.0]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+
+    // reference values from the SOFA test suite.
+    #[test]
+    fn test_nutation() {
+        let ttt = (2400000.5 + 53736.0 - 2451545.0) / 36525.0;
+        let (dpsi, deps) = nutation(ttt);
+
+        assert_diff(dpsi, -0.9643658353226564e-5, 1e-13);
+        assert_diff(deps, 0.4060051006879713e-4, 1e-13);
+    }
+
+    #[test]
+    fn test_obliquity() {
+        let ttt = (2400000.5 + 54388.0 - 2451545.0) / 36525.0;
+        assert_diff(mean_obliquity(ttt), 0.4090751347643816, 1e-14);
+    }
+}
@@ -0,0 +1,549 @@
+//! Vectors tagged with the reference frame they are expressed in, and conversions
+//! between TEME (SGP4 output), TOD, MOD and J2000, and the earth-fixed PEF and ITRF.
+//!
+//! The inertial conversions follow Vallado's IAU-76/FK5 reduction: IAU 1976 precession
+//! and IAU 1980 nutation, see [`iau1980`]. They are rotations only, so velocities are
+//! rotated like positions and the slow rates of precession and nutation are neglected.
+//!
+//! TEME and PEF differ by GMST, PEF and ITRF by polar motion. Earth-fixed velocities
+//! also include the rotation of the earth, so only [`State`] converts velocities
+//! between TEME and PEF.
+
+pub mod iau1980;
+
+use crate::constants::ARCSEC_TO_RAD;
+use crate::eop::{EopTable, NutationSeries};
+use crate::propogation::gstime::gstime_epoch;
+use crate::time::{Epoch, LeapSecondTable, TimeScale};
+use crate::{Mat3, Vec3};
+use std::marker::PhantomData;
+
+/// True equator, mean equinox of date: the frame SGP4 results are in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Teme;
+
+/// True equator and true equinox of date.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Tod;
+
+/// Mean equator and mean equinox of date.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Mod;
+
+/// Mean equator and mean equinox of J2000 (FK5).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct J2000;
+
+/// GCRF, as realized by the FK5 reduction with the IERS celestial pole offsets applied.
+///
+/// The offsets for IAU 1980 nutation absorb the frame bias between J2000 and GCRF, so
+/// this matches GCRF to the accuracy of the EOP data.
+pub type Gcrf = J2000;
+
+/// Pseudo earth-fixed: TEME rotated by GMST, with the true pole of date.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Pef;
+
+/// International terrestrial reference frame, earth-fixed with the IERS reference pole.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Itrf;
+
+/// Nominal rotation rate of the earth, in rad/s.
+pub const EARTH_ROTATION_RATE: f64 = 7.292115146706979e-5;
+
+/// A vector in frame `F`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FrameVector<F> {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    frame: PhantomData<F>,
+}
+
+/// Position (km) and velocity (km/s) in frame `F`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct State<F> {
+    pub position: FrameVector<F>,
+    pub velocity: FrameVector<F>,
+}
+
+impl<F> FrameVector<F> {
+    pub fn new(x: f64, y: f64, z: f64) -> FrameVector<F> {
+        FrameVector {
+            x,
+            y,
+            z,
+            frame: PhantomData,
+        }
+    }
+
+    /// Tag an untyped vector as being in frame `F`.
+    pub fn from_vec3(vector: &Vec3) -> FrameVector<F> {
+        FrameVector::new(vector.x, vector.y, vector.z)
+    }
+
+    pub fn to_vec3(&self) -> Vec3 {
+        Vec3 {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+
+    fn rotate<G>(&self, rotation: &Mat3) -> FrameVector<G> {
+        FrameVector::from_vec3(&(*rotation * self.to_vec3()))
+    }
+}
+
+impl<F> State<F> {
+    fn rotate<G>(&self, rotation: &Mat3) -> State<G> {
+        State {
+            position: self.position.rotate(rotation),
+            velocity: self.velocity.rotate(rotation),
+        }
+    }
+}
+
+/// The rotations of the FK5 reduction at one epoch.
+struct Reduction {
+    /// TEME to TOD, by the equation of the equinoxes.
+    equinox: Mat3,
+    /// MOD to TOD.
+    nutation: Mat3,
+    /// J2000 to MOD.
+    precession: Mat3,
+}
+
+/// Julian centuries of TT since J2000.
+fn tt_centuries(epoch: Epoch) -> f64 {
+    let (whole, fraction) = epoch.to_scale(TimeScale::Tt).jd_split();
+    ((whole - 2451545.0) + fraction) / 36525.0
+}
+
+fn reduction(epoch: Epoch, eop: Option<&EopTable>) -> Reduction {
+    let ttt = tt_centuries(epoch);
+    // dX and dY refer to the IAU 2000 models and do not correct IAU 1980 nutation.
+    let corrections = eop
+        .filter(|eop| eop.series() == NutationSeries::Iau1980)
+        .and_then(|eop| eop.at(epoch))
+        .unwrap_or_default();
+
+    let (dpsi, deps) = iau1980::nutation(ttt);
+    let dpsi = dpsi + corrections.dx * ARCSEC_TO_RAD;
+    let deps = deps + corrections.dy * ARCSEC_TO_RAD;
+    let mean_eps = iau1980::mean_obliquity(ttt);
+    let true_eps = mean_eps + deps;
+
+    let (zeta, theta, z) = iau1980::precession_angles(ttt);
+
+    Reduction {
+        // GMST is measured from the TEME x axis, so TEME differs from TOD by the whole
+        // of GAST - GMST. This keeps the reduction consistent with `teme_to_itrf`.
+        equinox: Mat3::rotation_z(-iau1980::equation_of_equinoxes(ttt, dpsi, mean_eps)),
+        nutation: Mat3::rotation_x(-true_eps)
+            * Mat3::rotation_z(-dpsi)
+            * Mat3::rotation_x(mean_eps),
+        precession: Mat3::rotation_z(-z) * Mat3::rotation_y(theta) * Mat3::rotation_z(-zeta),
+    }
+}
+
+// The same conversions are provided for single vectors and for states. `eop` supplies
+// the IERS corrections dψ and dε to the nutation when it is an IAU 1980 table
+// (`NutationSeries::Iau1980`); none are applied without one.
+macro_rules! frame_conversions {
+    ($type:ident) => {
+        impl $type<Teme> {
+            /// Convert to the true equator and true equinox of date.
+            pub fn to_tod(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<Tod> {
+                self.rotate(&reduction(epoch, eop).equinox)
+            }
+
+            /// Convert to J2000 through TOD and MOD.
+            pub fn to_j2000(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<J2000> {
+                let reduction = reduction(epoch, eop);
+                let rotation = reduction.precession.transpose()
+                    * reduction.nutation.transpose()
+                    * reduction.equinox;
+                self.rotate(&rotation)
+            }
+        }
+
+        impl $type<Tod> {
+            /// Convert to the true equator and mean equinox of date.
+            pub fn to_teme(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<Teme> {
+                self.rotate(&reduction(epoch, eop).equinox.transpose())
+            }
+
+            /// Convert to the mean equator and mean equinox of date.
+            pub fn to_mod(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<Mod> {
+                self.rotate(&reduction(epoch, eop).nutation.transpose())
+            }
+        }
+
+        impl $type<Mod> {
+            /// Convert to the true equator and true equinox of date.
+            pub fn to_tod(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<Tod> {
+                self.rotate(&reduction(epoch, eop).nutation)
+            }
+
+            /// Convert to the mean equator and mean equinox of J2000.
+            pub fn to_j2000(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<J2000> {
+                self.rotate(&reduction(epoch, eop).precession.transpose())
+            }
+        }
+
+        impl $type<J2000> {
+            /// Convert to the mean equator and mean equinox of date.
+            pub fn to_mod(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<Mod> {
+                self.rotate(&reduction(epoch, eop).precession)
+            }
+
+            /// Convert to TEME through MOD and TOD.
+            pub fn to_teme(&self, epoch: Epoch, eop: Option<&EopTable>) -> $type<Teme> {
+                let reduction = reduction(epoch, eop);
+                let rotation =
+                    reduction.equinox.transpose() * reduction.nutation * reduction.precession;
+                self.rotate(&rotation)
+            }
+        }
+    };
+}
+
+frame_conversions!(FrameVector);
+frame_conversions!(State);
+
+/// The orientation of the earth at one epoch.
+struct EarthOrientation {
+    /// TEME to PEF, by GMST.
+    sidereal: Mat3,
+    /// PEF to ITRF, by polar motion.
+    polar: Mat3,
+    /// Rotation rate of the earth, in rad/s.
+    omega: f64,
+}
+
+/// Orientation from the EOP table, or with zero DUT1, polar motion and excess length of
+/// day without one or outside it.
+fn earth_orientation(epoch: Epoch, eop: Option<&EopTable>) -> EarthOrientation {
+    let entry = eop.and_then(|eop| eop.at(epoch)).unwrap_or_default();
+    let gmst = gstime_epoch(epoch, LeapSecondTable::embedded(), entry.dut1);
+
+    EarthOrientation {
+        sidereal: Mat3::rotation_z(gmst),
+        polar: Mat3::rotation_y(-entry.xp * ARCSEC_TO_RAD)
+            * Mat3::rotation_x(-entry.yp * ARCSEC_TO_RAD),
+        omega: EARTH_ROTATION_RATE * (1.0 - entry.lod / 86400.0),
+    }
+}
+
+// Positions only; use `State` for velocities.
+impl FrameVector<Teme> {
+    /// Convert a position to the pseudo earth-fixed frame.
+    pub fn to_pef(&self, epoch: Epoch, eop: Option<&EopTable>) -> FrameVector<Pef> {
+        self.rotate(&earth_orientation(epoch, eop).sidereal)
+    }
+
+    /// Convert a position to ITRF through PEF.
+    pub fn to_itrf(&self, epoch: Epoch, eop: Option<&EopTable>) -> FrameVector<Itrf> {
+        let orientation = earth_orientation(epoch, eop);
+        self.rotate(&(orientation.polar * orientation.sidereal))
+    }
+}
+
+impl FrameVector<Pef> {
+    /// Convert a position to TEME.
+    pub fn to_teme(&self, epoch: Epoch, eop: Option<&EopTable>) -> FrameVector<Teme> {
+        self.rotate(&earth_orientation(epoch, eop).sidereal.transpose())
+    }
+
+    /// Convert a position to ITRF.
+    pub fn to_itrf(&self, epoch: Epoch, eop: Option<&EopTable>) -> FrameVector<Itrf> {
+        self.rotate(&earth_orientation(epoch, eop).polar)
+    }
+}
+
+impl FrameVector<Itrf> {
+    /// Convert a position to PEF.
+    pub fn to_pef(&self, epoch: Epoch, eop: Option<&EopTable>) -> FrameVector<Pef> {
+        self.rotate(&earth_orientation(epoch, eop).polar.transpose())
+    }
+
+    /// Convert a position to TEME through PEF.
+    pub fn to_teme(&self, epoch: Epoch, eop: Option<&EopTable>) -> FrameVector<Teme> {
+        let orientation = earth_orientation(epoch, eop);
+        self.rotate(&(orientation.sidereal.transpose() * orientation.polar.transpose()))
+    }
+}
+
+impl State<Teme> {
+    /// Convert to the pseudo earth-fixed frame, velocity relative to the rotating earth.
+    pub fn to_pef(&self, epoch: Epoch, eop: Option<&EopTable>) -> State<Pef> {
+        self.teme_to_pef(&earth_orientation(epoch, eop))
+    }
+
+    /// Convert to ITRF through PEF, velocity relative to the rotating earth.
+    pub fn to_itrf(&self, epoch: Epoch, eop: Option<&EopTable>) -> State<Itrf> {
+        let orientation = earth_orientation(epoch, eop);
+        self.teme_to_pef(&orientation).rotate(&orientation.polar)
+    }
+
+    fn teme_to_pef(&self, orientation: &EarthOrientation) -> State<Pef> {
+        let position = self.position.rotate::<Pef>(&orientation.sidereal);
+        let velocity = self.velocity.rotate::<Pef>(&orientation.sidereal);
+
+        // v_pef = v - ω × r, with ω along z.
+        State {
+            velocity: FrameVector::new(
+                velocity.x + orientation.omega * position.y,
+                velocity.y - orientation.omega * position.x,
+                velocity.z,
+            ),
+            position,
+        }
+    }
+}
+
+impl State<Pef> {
+    /// Convert to TEME, adding back the rotation of the earth to the velocity.
+    pub fn to_teme(&self, epoch: Epoch, eop: Option<&EopTable>) -> State<Teme> {
+        self.pef_to_teme(&earth_orientation(epoch, eop))
+    }
+
+    /// Convert to ITRF.
+    pub fn to_itrf(&self, epoch: Epoch, eop: Option<&EopTable>) -> State<Itrf> {
+        self.rotate(&earth_orientation(epoch, eop).polar)
+    }
+
+    fn pef_to_teme(&self, orientation: &EarthOrientation) -> State<Teme> {
+        let inertial = FrameVector::<Pef>::new(
+            self.velocity.x - orientation.omega * self.position.y,
+            self.velocity.y + orientation.omega * self.position.x,
+            self.velocity.z,
+        );
+
+        let sidereal = orientation.sidereal.transpose();
+        State {
+            position: self.position.rotate(&sidereal),
+            velocity: inertial.rotate(&sidereal),
+        }
+    }
+}
+
+impl State<Itrf> {
+    /// Convert to PEF.
+    pub fn to_pef(&self, epoch: Epoch, eop: Option<&EopTable>) -> State<Pef> {
+        self.rotate(&earth_orientation(epoch, eop).polar.transpose())
+    }
+
+    /// Convert to TEME through PEF, adding back the rotation of the earth to the velocity.
+    pub fn to_teme(&self, epoch: Epoch, eop: Option<&EopTable>) -> State<Teme> {
+        let orientation = earth_orientation(epoch, eop);
+        self.rotate::<Pef>(&orientation.polar.transpose())
+            .pef_to_teme(&orientation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eop::EopEntry;
+    use crate::tests::assert_diff;
+    use chrono::{Duration, TimeZone, Utc};
+
+    /// Vallado, Fundamentals of Astrodynamics and Applications, example 3-15.
+    fn example() -> (Epoch, EopTable, State<Teme>) {
+        let epoch = Epoch::from(
+            Utc.with_ymd_and_hms(2004, 4, 6, 7, 51, 28).unwrap()
+                + Duration::nanoseconds(386_009_000),
+        );
+
+        let corrections = EopEntry {
+            dx: -0.052195,
+            dy: -0.003875,
+            ..EopEntry::default()
+        };
+        let eop = EopTable::new(vec![
+            EopEntry {
+                mjd: 53101.0,
+                ..corrections
+            },
+            EopEntry {
+                mjd: 53102.0,
+                ..corrections
+            },
+        ])
+        .unwrap()
+        .with_series(NutationSeries::Iau1980);
+
+        let teme = State {
+            position: FrameVector::new(5094.18016210, 6127.64465950, 6380.34453270),
+            velocity: FrameVector::new(-4.746131487, 0.785818041, 5.531931288),
+        };
+
+        (epoch, eop, teme)
+    }
+
+    fn assert_vector<F>(vector: &FrameVector<F>, expected: [f64; 3], epsilon: f64) {
+        assert_diff(vector.x, expected[0], epsilon);
+        assert_diff(vector.y, expected[1], epsilon);
+        assert_diff(vector.z, expected[2], epsilon);
+    }
+
+    #[test]
+    fn test_teme_to_gcrf() {
+        let (epoch, eop, teme) = example();
+
+        let gcrf: State<Gcrf> = teme.to_j2000(epoch, Some(&eop));
+        assert_vector(
+            &gcrf.position,
+            [5102.508958, 6123.011401, 6378.136928],
+            1e-6,
+        );
+        assert_vector(
+            &gcrf.velocity,
+            [-4.743220157, 0.790536497, 5.533755727],
+            1e-9,
+        );
+
+        let tod = teme.to_tod(epoch, Some(&eop));
+        assert_vector(
+            &tod.position,
+            [5094.51620300, 6127.36527840, 6380.34453270],
+            1e-6,
+        );
+
+        let mod_ = tod.to_mod(epoch, Some(&eop));
+        assert_vector(
+            &mod_.position,
+            [5094.02837450, 6127.87081640, 6380.24851640],
+            1e-6,
+        );
+
+        let stepwise = mod_.to_j2000(epoch, Some(&eop));
+        assert_vector(
+            &stepwise.position,
+            [gcrf.position.x, gcrf.position.y, gcrf.position.z],
+            1e-9,
+        );
+
+        // the same offsets as IAU 2000 dX and dY do not change IAU 1980 nutation.
+        let iau2000 = eop.with_series(NutationSeries::Iau2000);
+        assert_eq!(
+            teme.to_j2000(epoch, Some(&iau2000)),
+            teme.to_j2000(epoch, None)
+        );
+        assert!(gcrf.position != teme.to_j2000(epoch, None).position);
+    }
+
+    #[test]
+    fn test_teme_to_itrf() {
+        let (epoch, _, teme) = example();
+
+        let orientation = EopEntry {
+            xp: -0.140682,
+            yp: 0.333309,
+            dut1: -0.4399619,
+            lod: 0.0015563,
+            ..EopEntry::default()
+        };
+        let eop = EopTable::new(vec![
+            EopEntry {
+                mjd: 53101.0,
+                ..orientation
+            },
+            EopEntry {
+                mjd: 53102.0,
+                ..orientation
+            },
+        ])
+        .unwrap();
+
+        let pef = teme.to_pef(epoch, Some(&eop));
+        assert_vector(
+            &pef.position,
+            [-1033.4750313, 7901.3055856, 6380.3445327],
+            1e-6,
+        );
+        assert_vector(
+            &pef.velocity,
+            [-3.225632747, -2.872442511, 5.531931288],
+            1e-9,
+        );
+
+        let itrf = teme.to_itrf(epoch, Some(&eop));
+        assert_vector(
+            &itrf.position,
+            [-1033.4793830, 7901.2952754, 6380.3565958],
+            1e-6,
+        );
+        assert_vector(
+            &itrf.velocity,
+            [-3.225636520, -2.872451450, 5.531924446],
+            1e-9,
+        );
+        assert_eq!(pef.to_itrf(epoch, Some(&eop)), itrf);
+
+        let back = itrf.to_teme(epoch, Some(&eop));
+        assert_vector(
+            &back.position,
+            [teme.position.x, teme.position.y, teme.position.z],
+            1e-9,
+        );
+        assert_vector(
+            &back.velocity,
+            [teme.velocity.x, teme.velocity.y, teme.velocity.z],
+            1e-12,
+        );
+
+        let position = teme.position.to_itrf(epoch, Some(&eop));
+        assert_vector(
+            &position,
+            [itrf.position.x, itrf.position.y, itrf.position.z],
+            1e-9,
+        );
+        assert_vector(
+            &position.to_teme(epoch, Some(&eop)),
+            [teme.position.x, teme.position.y, teme.position.z],
+            1e-9,
+        );
+
+        // an object fixed to the earth has no earth-fixed velocity.
+        let fixed = State::<Itrf> {
+            position: itrf.position,
+            velocity: FrameVector::new(0.0, 0.0, 0.0),
+        };
+        let inertial = fixed.to_teme(epoch, None);
+        let speed = (inertial.velocity.x.powi(2) + inertial.velocity.y.powi(2)).sqrt();
+        let radius = (inertial.position.x.powi(2) + inertial.position.y.powi(2)).sqrt();
+        assert_diff(speed, EARTH_ROTATION_RATE * radius, 1e-12);
+        assert_vector(
+            &inertial.to_itrf(epoch, None).velocity,
+            [0.0, 0.0, 0.0],
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (epoch, _, teme) = example();
+
+        let j2000 = teme.position.to_j2000(epoch, None);
+        let back = j2000
+            .to_mod(epoch, None)
+            .to_tod(epoch, None)
+            .to_teme(epoch, None);
+        assert_vector(
+            &back,
+            [teme.position.x, teme.position.y, teme.position.z],
+            1e-9,
+        );
+        assert_eq!(
+            j2000.to_teme(epoch, None),
+            teme.position.to_j2000(epoch, None).to_teme(epoch, None)
+        );
+
+        // the equation of the equinoxes is a rotation about z.
+        let tod = teme.position.to_tod(epoch, None);
+        assert_eq!(tod.z, teme.position.z);
+    }
+}
@@ -0,0 +1,222 @@
+//! Elevation masks: the lowest elevation at which a satellite is visible, as a function
+//! of azimuth.
+//!
+//! A scalar `f64` is a mask of the same elevation at every azimuth. A [`HorizonMask`]
+//! follows the terrain and buildings around a station, interpolated linearly between
+//! surveyed points.
+
+use crate::constants::*;
+use crate::Bearing;
+
+#[derive(Debug, PartialEq)]
+pub enum HorizonMaskError {
+    /// A line could not be read (line number, line).
+    InvalidLine(usize, String),
+    /// The mask has no points.
+    Empty,
+    /// The file could not be read.
+    Io(String),
+}
+
+/// Minimum elevation for visibility as a function of azimuth.
+pub trait ElevationMask {
+    /// Minimum elevation, in radians, at an azimuth in radians clockwise from north.
+    fn min_elevation(&self, azimuth: f64) -> f64;
+
+    /// Whether look angles (as from [`ecf_to_look_angles`](crate::transforms::ecf_to_look_angles))
+    /// are at or above the mask.
+    fn is_visible(&self, look_angles: &Bearing) -> bool {
+        look_angles.elevation >= self.min_elevation(look_angles.azimuth)
+    }
+
+    /// Azimuths where the mask has a corner or a step. Pass prediction splits its search
+    /// where the satellite crosses them.
+    fn breaks(&self) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+/// The same minimum elevation, in radians, at every azimuth.
+impl ElevationMask for f64 {
+    fn min_elevation(&self, _azimuth: f64) -> f64 {
+        *self
+    }
+}
+
+impl<M: ElevationMask + ?Sized> ElevationMask for &M {
+    fn min_elevation(&self, azimuth: f64) -> f64 {
+        (**self).min_elevation(azimuth)
+    }
+
+    fn breaks(&self) -> Vec<f64> {
+        (**self).breaks()
+    }
+}
+
+/// Horizon elevation surveyed at a set of azimuths, interpolated linearly between them
+/// and across north.
+///
+/// Two points at the same azimuth make a step, as at the edge of a building: the later
+/// one applies from that azimuth on.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HorizonMask {
+    /// (azimuth, elevation) in radians, in increasing order of azimuth in [0, 2π).
+    points: Vec<(f64, f64)>,
+}
+
+impl HorizonMask {
+    /// Mask from (azimuth, elevation) points in radians, in any order.
+    pub fn new(points: Vec<(f64, f64)>) -> Result<HorizonMask, HorizonMaskError> {
+        if points.is_empty() {
+            return Err(HorizonMaskError::Empty);
+        }
+
+        let mut points = points
+            .into_iter()
+            .map(|(azimuth, elevation)| (azimuth.rem_euclid(TWO_PI), elevation))
+            .collect::<Vec<_>>();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(HorizonMask { points })
+    }
+
+    /// Parse a mask with one `azimuth, elevation` point per line, in degrees.
+    ///
+    /// Values may be separated by commas, semicolons, tabs or spaces. Blank lines, lines
+    /// starting with `#` and a header line before the first point are skipped.
+    pub fn parse(text: &str) -> Result<HorizonMask, HorizonMaskError> {
+        let mut points = vec![];
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = line
+                .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+                .filter(|field| !field.is_empty())
+                .collect::<Vec<&str>>();
+
+            let values = fields
+                .iter()
+                .map(|field| field.parse::<f64>().ok())
+                .collect::<Option<Vec<f64>>>();
+
+            match values.as_deref() {
+                Some(&[azimuth, elevation])
+                    if azimuth.is_finite() && (-90.0..=90.0).contains(&elevation) =>
+                {
+                    points.push((azimuth * DEG_2_RAD, elevation * DEG_2_RAD))
+                }
+                None if points.is_empty() && fields[0].parse::<f64>().is_err() => continue,
+                _ => return Err(HorizonMaskError::InvalidLine(number + 1, line.to_string())),
+            }
+        }
+
+        HorizonMask::new(points)
+    }
+
+    /// Read and parse a mask file from disk.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<HorizonMask, HorizonMaskError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => HorizonMask::parse(&text),
+            Err(err) => Err(HorizonMaskError::Io(err.to_string())),
+        }
+    }
+
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+}
+
+impl ElevationMask for HorizonMask {
+    fn min_elevation(&self, azimuth: f64) -> f64 {
+        let azimuth = azimuth.rem_euclid(TWO_PI);
+        let points = &self.points;
+        let last = points.len() - 1;
+
+        // the points either side of the azimuth, wrapping around north.
+        let next = points.partition_point(|point| point.0 <= azimuth);
+        let (a, b) = match next {
+            0 => ((points[last].0 - TWO_PI, points[last].1), points[0]),
+            n if n > last => (points[last], (points[0].0 + TWO_PI, points[0].1)),
+            n => (points[n - 1], points[n]),
+        };
+
+        a.1 + (b.1 - a.1) * (azimuth - a.0) / (b.0 - a.0)
+    }
+
+    fn breaks(&self) -> Vec<f64> {
+        let mut breaks = self.points.iter().map(|point| point.0).collect::<Vec<_>>();
+        breaks.dedup();
+        breaks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+
+    #[test]
+    fn test_parse() {
+        let text = "\
+# station horizon survey
+azimuth_deg,elevation_deg
+0, 5
+90, 15
+
+180;20
+270\t10
+270\t30
+";
+        let mask = HorizonMask::parse(text).unwrap();
+        assert_eq!(mask.points().len(), 5);
+
+        let at = |azimuth: f64| mask.min_elevation(azimuth * DEG_2_RAD) * RAD_TO_DEG;
+        assert_diff(at(0.0), 5.0, 1e-12);
+        assert_diff(at(45.0), 10.0, 1e-12);
+        assert_diff(at(135.0), 17.5, 1e-12);
+        assert_diff(at(269.0), 10.0 + 10.0 / 90.0, 1e-12);
+        assert_diff(at(270.0), 30.0, 1e-12);
+        assert_diff(at(315.0), 17.5, 1e-12);
+        assert_diff(at(-45.0), 17.5, 1e-12);
+        assert_diff(at(360.0), 5.0, 1e-12);
+
+        assert_eq!(
+            HorizonMask::parse("0, 5\n90, high\n"),
+            Err(HorizonMaskError::InvalidLine(2, "90, high".to_string()))
+        );
+        assert_eq!(
+            HorizonMask::parse("0, 95\n"),
+            Err(HorizonMaskError::InvalidLine(1, "0, 95".to_string()))
+        );
+        assert_eq!(
+            HorizonMask::parse("# nothing\n"),
+            Err(HorizonMaskError::Empty)
+        );
+    }
+
+    #[test]
+    fn test_single_point() {
+        let mask = HorizonMask::new(vec![(1.0, 0.1)]).unwrap();
+        for &azimuth in &[0.0, 1.0, 3.0, 6.0] {
+            assert_diff(mask.min_elevation(azimuth), 0.1, 1e-15);
+        }
+    }
+
+    #[test]
+    fn test_is_visible() {
+        let mask = HorizonMask::new(vec![(0.0, 0.0), (PI, 0.5)]).unwrap();
+        let look = |azimuth, elevation| Bearing {
+            azimuth,
+            elevation,
+            range: 1000.0,
+        };
+
+        assert!(mask.is_visible(&look(0.1, 0.1)));
+        assert!(!mask.is_visible(&look(PI, 0.4)));
+        assert!(0.3f64.is_visible(&look(PI, 0.4)));
+    }
+}
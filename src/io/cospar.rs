@@ -0,0 +1,133 @@
+//! COSPAR international designators (e.g. 1998-067A).
+
+use std::fmt;
+
+use crate::io::SatrecParseError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// International designator: launch year, launch number of the year and piece.
+pub struct InternationalDesignator {
+    /// Four digit launch year.
+    pub launch_year: u32,
+
+    /// Launch number within the year.
+    pub launch_number: u32,
+
+    /// Piece of the launch, `A` for the primary payload.
+    pub piece: String,
+}
+
+impl InternationalDesignator {
+    /// Parse the eight TLE columns (`98067A  `). Returns `None` for a blank field.
+    ///
+    /// Two digit years below 57 are taken to be in the 2000s, as for TLE epochs.
+    pub fn from_tle(text: &str) -> Result<Option<InternationalDesignator>, SatrecParseError> {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+
+        let invalid = || SatrecParseError::CompoundError("intldesg", text.to_string());
+
+        if trimmed.len() < 6 || !trimmed.is_char_boundary(5) {
+            return Err(invalid());
+        }
+
+        let year = trimmed[0..2].parse::<u32>().map_err(|_| invalid())?;
+        let launch_number = trimmed[2..5].trim().parse::<u32>().map_err(|_| invalid())?;
+        let piece = &trimmed[5..];
+
+        if !piece.bytes().all(|c| c.is_ascii_uppercase()) {
+            return Err(invalid());
+        }
+
+        let launch_year = if year < 57 { year + 2000 } else { year + 1900 };
+
+        Ok(Some(InternationalDesignator {
+            launch_year,
+            launch_number,
+            piece: piece.to_string(),
+        }))
+    }
+
+    /// Parse the OMM `OBJECT_ID` form (`1998-067A`).
+    pub fn from_omm(text: &str) -> Result<InternationalDesignator, SatrecParseError> {
+        let trimmed = text.trim();
+        let invalid = || SatrecParseError::OmmInvalidField("OBJECT_ID", text.to_string());
+
+        let mut parts = trimmed.splitn(2, '-');
+        let year = parts.next().ok_or_else(invalid)?;
+        let rest = parts.next().ok_or_else(invalid)?;
+
+        if year.len() != 4 || rest.len() < 4 || !rest.is_char_boundary(3) {
+            return Err(invalid());
+        }
+
+        let piece = &rest[3..];
+        if !piece.bytes().all(|c| c.is_ascii_uppercase()) {
+            return Err(invalid());
+        }
+
+        Ok(InternationalDesignator {
+            launch_year: year.parse::<u32>().map_err(|_| invalid())?,
+            launch_number: rest[..3].parse::<u32>().map_err(|_| invalid())?,
+            piece: piece.to_string(),
+        })
+    }
+
+    /// Format for the eight TLE columns, e.g. `98067A  `.
+    pub fn to_tle(&self) -> String {
+        format!(
+            "{:02}{:03}{:<3}",
+            self.launch_year % 100,
+            self.launch_number,
+            self.piece
+        )
+    }
+}
+
+impl fmt::Display for InternationalDesignator {
+    /// Formats in the OMM/COSPAR form, e.g. `1998-067A`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:03}{}",
+            self.launch_year, self.launch_number, self.piece
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::cospar::*;
+
+    #[test]
+    fn test_from_tle() {
+        let iss = InternationalDesignator::from_tle("98067A  ").unwrap().unwrap();
+        assert_eq!(iss.launch_year, 1998);
+        assert_eq!(iss.launch_number, 67);
+        assert_eq!(iss.piece, "A");
+        assert_eq!(iss.to_string(), "1998-067A");
+        assert_eq!(iss.to_tle(), "98067A  ");
+
+        let debris = InternationalDesignator::from_tle("85108AA ").unwrap().unwrap();
+        assert_eq!(debris.launch_year, 1985);
+        assert_eq!(debris.piece, "AA");
+
+        let recent = InternationalDesignator::from_tle("06022G  ").unwrap().unwrap();
+        assert_eq!(recent.launch_year, 2006);
+
+        assert_eq!(InternationalDesignator::from_tle("        "), Ok(None));
+        assert!(InternationalDesignator::from_tle("98O67A  ").is_err());
+    }
+
+    #[test]
+    fn test_from_omm() {
+        let iss = InternationalDesignator::from_omm("1998-067A").unwrap();
+        assert_eq!(Some(iss.clone()), InternationalDesignator::from_tle("98067A").unwrap());
+        assert_eq!(iss.to_string(), "1998-067A");
+
+        assert!(InternationalDesignator::from_omm("98067A").is_err());
+        assert!(InternationalDesignator::from_omm("1998-67").is_err());
+    }
+}
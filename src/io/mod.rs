@@ -5,13 +5,33 @@ use crate::propogation::initl::*;
 use crate::propogation::sgp4init;
 use crate::propogation::sgp4::*;
 
+pub mod cospar;
+pub mod omm;
+pub mod satnum;
+pub mod tle;
+
+use self::cospar::InternationalDesignator;
+use self::satnum::CatalogNumber;
+
 #[derive(Clone)]
 pub struct Satrec {
     pub name: Option<String>,
     pub init: DpperInit,
     pub operationmode: DpperOpsMode,
+    /// Gravity constants the satrec was initialized with.
+    pub gravity_model: GravityModel,
     pub error: u32,
-    pub satnum: String,
+    pub satnum: CatalogNumber,
+    /// Security classification: `U` (unclassified), `C` or `S`.
+    pub classification: char,
+    /// COSPAR international designator, if the element set has one.
+    pub intldesg: Option<InternationalDesignator>,
+    /// Ephemeris type, 0 for elements intended for SGP4.
+    pub ephtype: i64,
+    /// Element set number, incremented by the issuer for each new element set.
+    pub elnum: i64,
+    /// Revolution number at epoch.
+    pub revnum: i64,
     pub epochyr: i64,
     pub epochdays: f64,
     pub ndot: f64,
@@ -22,6 +42,7 @@ pub struct Satrec {
     pub ecco: f64,
     pub mo: f64,
     pub no: f64,
+    pub no_kozai: f64,
     pub a: f64,
     pub argpo: f64,
     pub alta: f64,
@@ -122,8 +143,14 @@ impl Satrec {
             name: None,
             init: DpperInit::Y,
             operationmode: DpperOpsMode::I,
+            gravity_model: GravityModel::Wgs84,
             error: 0,
-            satnum: String::new(),
+            satnum: CatalogNumber(0),
+            classification: 'U',
+            intldesg: None,
+            ephtype: 0,
+            elnum: 0,
+            revnum: 0,
             epochyr: 0,
             epochdays: 0.0,
             ndot: 0.0,
@@ -134,6 +161,7 @@ impl Satrec {
             ecco: 0.0,
             mo: 0.0,
             no: 0.0,
+            no_kozai: 0.0,
             a: 0.0,
             argpo: 0.0,
             alta: 0.0,
@@ -228,6 +256,20 @@ impl Satrec {
             xni: 0.0,
         }
     }
+
+    /// Epoch of the element set, as the UTC time of `jdsatepoch`.
+    ///
+    /// This is the epoch propagation counts minutes from, so `epoch() + tsince` is the
+    /// time of the state `sgp4` returns for `tsince`.
+    ///
+    /// # Panics
+    ///
+    /// If `jdsatepoch` is outside the range of `DateTime`, which no parsed element set is.
+    pub fn epoch(&self) -> chrono::DateTime<chrono::Utc> {
+        crate::time::Epoch::from_jd(self.jdsatepoch, crate::time::TimeScale::Utc)
+            .to_datetime()
+            .expect("satrec epoch outside the range of DateTime")
+    }
 }
 
 pub fn parse_multiple(string: &str) -> (Vec<Satrec>, Vec<SatrecParseError>) {
@@ -241,7 +283,7 @@ pub fn parse_multiple(string: &str) -> (Vec<Satrec>, Vec<SatrecParseError>) {
             match twoline2satrec(lines[i+1], lines[i+2]) {
                 Ok(mut rec) => {
                     if (lines[i].bytes().collect::<Vec<u8>>()[0] == '0' as u8) {
-                        rec.name = Some(lines[i][2..].trim().to_string());
+                        rec.name = Some(lines[i].get(2..).unwrap_or("").trim().to_string());
                     } else {
                         rec.name = Some(lines[i].trim().to_string());
                     }
@@ -287,7 +329,7 @@ pub fn parse(string: &str) -> Result<Satrec, SatrecParseError> {
         match satrec {
             Ok(mut satrec) => {
                 if (lines[0].bytes().collect::<Vec<u8>>()[0] == '0' as u8) {
-                    satrec.name = Some(lines[0][2..].to_string());
+                    satrec.name = Some(lines[0].get(2..).unwrap_or("").to_string());
                 } else {
                     satrec.name = Some(lines[0].to_string());
                 }
@@ -309,19 +351,51 @@ pub fn parse(string: &str) -> Result<Satrec, SatrecParseError> {
 }
 
 pub fn twoline2satrec(str1: &str, str2: &str) -> Result<Satrec, SatrecParseError> {
-    let mut satrec = parse_satrec(str1, str2)?;
+    twoline2satrec_with_options(str1, str2, &InitOptions::default())
+}
+
+/// Options used when initializing SGP4 for a parsed element set.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct InitOptions {
+    /// Earth gravity constants, WGS-84 unless specified.
+    pub gravity_model: GravityModel,
+    /// AFSPC compatible (`A`) or improved (`I`, the default) operation mode.
+    pub opsmode: DpperOpsMode,
+}
+
+/// Parse and initialize a TLE, see `InitOptions`.
+pub fn twoline2satrec_with_options(
+    str1: &str,
+    str2: &str,
+    options: &InitOptions,
+) -> Result<Satrec, SatrecParseError> {
+    let satrec = parse_satrec(str1, str2)?;
+
+    init_satrec_with_options(satrec, options)
+}
 
-    let opsmode = crate::propogation::dpper::DpperOpsMode::I;
+/// Run sgp4init on a satrec whose mean elements have been filled in by a parser.
+pub(crate) fn init_satrec(satrec: Satrec) -> Result<Satrec, SatrecParseError> {
+    init_satrec_with_options(satrec, &InitOptions::default())
+}
+
+/// Run sgp4init with the given options on a parsed satrec.
+///
+/// This can also be used to re-initialize a satrec, e.g. one read from an OMM, with a
+/// different gravity model.
+pub fn init_satrec_with_options(mut satrec: Satrec, options: &InitOptions) -> Result<Satrec, SatrecParseError> {
     let opts = crate::propogation::sgp4init::SGP4InitOptions {
-        opsmode: opsmode,
-        satn: satrec.satnum.clone(),
+        opsmode: options.opsmode,
+        gravity_model: options.gravity_model,
+        satn: satrec.satnum,
         epoch: satrec.jdsatepoch - 2433281.5,
         xbstar: satrec.bstar,
         xecco: satrec.ecco,
         xargpo: satrec.argpo,
         xinclo: satrec.inclo,
         xmo: satrec.mo,
-        xno: satrec.no,
+        // sgp4init replaces `no` with the un-kozai'd value, start from the original.
+        xno: satrec.no_kozai,
         xnodeo: satrec.nodeo,
     };
 
@@ -330,7 +404,7 @@ pub fn twoline2satrec(str1: &str, str2: &str) -> Result<Satrec, SatrecParseError
         Err(err) => return Err(SatrecParseError::Sgp4InitError(err))
     };
 
-    return Ok(satrec);
+    Ok(satrec)
 }
 
 #[derive(Debug, PartialEq)]
@@ -341,7 +415,15 @@ pub enum SatrecParseError {
     Sgp4InitError(SGP4Error),
     InvalidTLELineCheckFailed,
     InvalidTLEBadLineCount,
-    SatrecMultiError(usize, Box<SatrecParseError>)
+    InvalidCatalogNumber(String),
+    SatrecMultiError(usize, Box<SatrecParseError>),
+    /// A TLE column check failed: line number (1 or 2), first and last column (1-based,
+    /// inclusive, as in the TLE specification), expected text and found text.
+    InvalidTLEField(usize, usize, usize, String, String),
+    OmmSyntaxError(String),
+    OmmMissingField(&'static str),
+    OmmInvalidField(&'static str, String),
+    OmmUnsupportedTheory(String),
 }
 
 
@@ -359,9 +441,96 @@ fn parse_int(line: &str, name: &'static str, low: usize, high: usize) -> Result<
     }
 }
 
+/// Parse an integer from columns that may be blank or missing from a short line (as 0).
+fn parse_optional_int(line: &str, name: &'static str, low: usize, high: usize) -> Result<i64, SatrecParseError> {
+    let text = line.get(low..high.min(line.len())).unwrap_or("").trim();
+    if text.is_empty() {
+        return Ok(0);
+    }
+
+    match text.parse::<i64>() {
+        Ok(res) => Ok(res),
+        Err(_) => Err(SatrecParseError::IntParseError(name, low, high, text.to_string()))
+    }
+}
+
+/// Parse a field in the TLE implied-decimal notation, e.g. " 12345-4" = 0.12345e-4.
+fn parse_implied_decimal(line: &str, name: &'static str, low: usize, high: usize) -> Result<f64, SatrecParseError> {
+    let mantissa = line[low..high - 2].trim();
+    let exponent = line[high - 2..high].trim();
+
+    let (sign, digits) = if mantissa.starts_with('-') || mantissa.starts_with('+') {
+        mantissa.split_at(1)
+    } else {
+        ("", mantissa)
+    };
+
+    let compound = format!("{}.{}E{}", sign, digits, exponent);
+    match compound.parse::<f64>() {
+        Ok(res) => Ok(res),
+        Err(_) => Err(SatrecParseError::CompoundError(name, compound))
+    }
+}
+
+/// How strictly a TLE is checked before it is parsed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TleValidation {
+    /// Any problem found by `tle::validate_tle` rejects the TLE.
+    Strict,
+    /// Problems are returned as warnings and parsing continues where possible.
+    Lenient,
+}
+
+/// Parse a TLE after validating its structure, see `tle::validate_tle`.
+///
+/// In strict mode the first problem found is returned as the error. In lenient mode the
+/// problems are returned alongside the satrec as warnings.
+pub fn parse_satrec_validated(
+    str1: &str,
+    str2: &str,
+    mode: TleValidation,
+) -> Result<(Satrec, Vec<SatrecParseError>), SatrecParseError> {
+    let mut warnings = tle::validate_tle(str1, str2);
+
+    if mode == TleValidation::Strict && !warnings.is_empty() {
+        return Err(warnings.remove(0));
+    }
+
+    let satrec = parse_satrec(str1, str2)?;
+
+    Ok((satrec, warnings))
+}
+
+/// Validate, parse and initialize a TLE. See `parse_satrec_validated`.
+pub fn twoline2satrec_validated(
+    str1: &str,
+    str2: &str,
+    mode: TleValidation,
+) -> Result<(Satrec, Vec<SatrecParseError>), SatrecParseError> {
+    let (satrec, warnings) = parse_satrec_validated(str1, str2, mode)?;
+
+    Ok((init_satrec(satrec)?, warnings))
+}
+
 pub fn parse_satrec(str1: &str, str2: &str) -> Result<Satrec, SatrecParseError> {
+    // Only the columns read below need to be present.
+    tle::check_ascii(str1, 1)?;
+    tle::check_ascii(str2, 2)?;
+    tle::check_length(str1, 1, 61, None)?;
+    tle::check_length(str2, 2, 63, None)?;
+
     // Parse sat num
-    let satnum = str1[2..7].trim();
+    let satnum = CatalogNumber::from_alpha5(&str1[2..7])?;
+
+    // Parse metadata
+    let classification = match str1.as_bytes()[7] {
+        b' ' => 'U',
+        c => c as char,
+    };
+    let intldesg = InternationalDesignator::from_tle(&str1[9..17])?;
+    let ephtype = parse_optional_int(str1, "ephtype", 62, 63)?;
+    let elnum = parse_optional_int(str1, "elnum", 64, 68)?;
+    let revnum = parse_optional_int(str2, "revnum", 63, 68)?;
 
     // Parse epoch
     let epochyr = parse_int(str1, "epochyr", 18, 20)?;
@@ -371,25 +540,10 @@ pub fn parse_satrec(str1: &str, str2: &str) -> Result<Satrec, SatrecParseError>
     let ndot = parse_float(str1, "ndot", 33, 43)?  / (XPDOTP * 1440.0);;
 
     // Parse nndot
-    let nddot_0 = parse_int(str1, "nndot frac", 44, 50)?;
-    let nddot_1 = str1[50..52].to_string();
-    let nndot_str = format!(".{}E{}", nddot_0, nddot_1);
-
-    let nddot = match nndot_str.parse::<f64>() {
-            Ok(res) => res / (XPDOTP * 1440.0 * 1440.0),
-            Err(_) => return Err(SatrecParseError::CompoundError("nndot", nndot_str))
-    };
+    let nddot = parse_implied_decimal(str1, "nndot", 44, 52)? / (XPDOTP * 1440.0 * 1440.0);
 
     // Parse bstar
-    let bstar_0 = str1[53..54].to_string();
-    let bstar_1 = parse_int(str1, "bstar frac", 54, 59)?;
-    let bstar_2 = str1[59..61].to_string();
-    let bstar_str = format!("{}.{}E{}", bstar_0, bstar_1, bstar_2);
-
-    let bstar = match bstar_str.trim().parse::<f64>() {
-        Ok(res) => res,
-        Err(_) => return Err(SatrecParseError::CompoundError("bstr", bstar_str))
-    };
+    let bstar = parse_implied_decimal(str1, "bstr", 53, 61)?;
 
     let inclo = parse_float(str2, "inclo", 8, 16)? * DEG_2_RAD;
     let nodeo = parse_float(str2, "nodeo", 17, 25)? * DEG_2_RAD;
@@ -410,29 +564,17 @@ pub fn parse_satrec(str1: &str, str2: &str) -> Result<Satrec, SatrecParseError>
     let alta = (a * (1.0 + ecco)) - 1.0;
     let altp = (a * (1.0 - ecco)) - 1.0;
 
-    let year;
-    if epochyr < 57 {
-        year = epochyr + 2000;
-    } else {
-        year = epochyr + 1900;
-    }
-
-    let jd = ext::days2mdhms(year as u64, epochdays);
-
-    let jdsatepoch = ext::jday(
-        year as f64,
-        jd.month,
-        jd.day,
-        jd.hour,
-        jd.minute,
-        jd.second,
-        0.0,
-    );
+    let jdsatepoch = epoch_to_jd(epochyr, epochdays);
 
     let mut satrec = Satrec::zero();
 
     satrec.operationmode = DpperOpsMode::I;
-    satrec.satnum = satnum.to_string();
+    satrec.satnum = satnum;
+    satrec.classification = classification;
+    satrec.intldesg = intldesg;
+    satrec.ephtype = ephtype;
+    satrec.elnum = elnum;
+    satrec.revnum = revnum;
     satrec.epochyr = epochyr;
     satrec.epochdays = epochdays;
     satrec.ndot = ndot;
@@ -444,6 +586,7 @@ pub fn parse_satrec(str1: &str, str2: &str) -> Result<Satrec, SatrecParseError>
     satrec.argpo = argpo;
     satrec.mo = mo;
     satrec.no = no;
+    satrec.no_kozai = no;
     satrec.a = a;
     satrec.alta = alta;
     satrec.altp = altp;
@@ -452,6 +595,32 @@ pub fn parse_satrec(str1: &str, str2: &str) -> Result<Satrec, SatrecParseError>
     return Ok(satrec);
 }
 
+/// Convert a two-digit epoch year and fractional day of year into a julian date.
+pub(crate) fn epoch_to_jd(epochyr: i64, epochdays: f64) -> f64 {
+    let year = if epochyr < 57 {
+        epochyr + 2000
+    } else {
+        epochyr + 1900
+    };
+
+    day_of_year_to_jd(year, epochdays)
+}
+
+/// Convert a four-digit year and fractional day of year into a julian date.
+pub(crate) fn day_of_year_to_jd(year: i64, epochdays: f64) -> f64 {
+    let jd = ext::days2mdhms(year as u64, epochdays);
+
+    ext::jday(
+        year as f64,
+        jd.month,
+        jd.day,
+        jd.hour,
+        jd.minute,
+        jd.second,
+        0.0,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -463,6 +632,11 @@ mod tests {
 "###;
         let sat = crate::io::parse(element).unwrap();
         assert_eq!(sat.name, Some("ISS (ZARYA)".to_string()));
+        assert_eq!(sat.classification, 'U');
+        assert_eq!(sat.intldesg.unwrap().to_string(), "1998-067A");
+        assert_eq!(sat.ephtype, 0);
+        assert_eq!(sat.elnum, 999);
+        assert_eq!(sat.revnum, 16246);
     }
 
         #[test]
@@ -481,7 +655,7 @@ mod tests {
         ).unwrap();
 
         assert_eq!(satrec.error, 0);
-        assert_eq!(satrec.satnum, "88888");
+        assert_eq!(satrec.satnum, crate::io::satnum::CatalogNumber(88888));
         assert_eq!(satrec.epochyr, 80);
         assert_eq!(satrec.epochdays, 275.98708465);
         assert_eq!(satrec.ndot, 2.2148107004387767e-9);
@@ -508,7 +682,7 @@ mod tests {
 //         ).unwrap()[0];
 
 //         assert_eq!(satrec.error, 0);
-//         assert_eq!(satrec.satnum, "88888");
+//         assert_eq!(satrec.satnum, crate::io::satnum::CatalogNumber(88888));
 //         assert_eq!(satrec.epochyr, 80);
 //         assert_eq!(satrec.epochdays, 275.98708465);
 //         assert_eq!(satrec.ndot, 2.2148107004387767e-9);
@@ -534,7 +708,7 @@ mod tests {
         ).unwrap();
 
         assert_eq!(satrec.error, 0);
-        assert_eq!(satrec.satnum, "88888");
+        assert_eq!(satrec.satnum, crate::io::satnum::CatalogNumber(88888));
         assert_eq!(satrec.epochyr, 80);
         assert_eq!(satrec.epochdays, 275.98708465);
         assert_eq!(satrec.ndot, 2.2148107004387767e-9);
@@ -635,4 +809,76 @@ mod tests {
         assert_eq!(satrec.xli, 0.0);
         assert_eq!(satrec.xni, 0.0);
     }
+
+    #[test]
+    fn test_gravity_models() {
+        use crate::constants::GravityModel;
+        use crate::io::{init_satrec_with_options, twoline2satrec_with_options, InitOptions};
+        use crate::propogation::sgp4::sgp4;
+        use crate::tests::assert_diff;
+
+        let tle1 = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+        let tle2 = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+        // Vallado's tcppver.out is generated with WGS-72.
+        let options = InitOptions { gravity_model: GravityModel::Wgs72, ..InitOptions::default() };
+        let mut satrec = twoline2satrec_with_options(tle1, tle2, &options).unwrap();
+        assert_eq!(satrec.gravity_model, GravityModel::Wgs72);
+
+        let result = sgp4(&mut satrec, 360.0).unwrap();
+        assert_diff(result.position.x, -7154.03120202, 1e-6);
+        assert_diff(result.position.y, -3783.17682504, 1e-6);
+        assert_diff(result.position.z, -3536.19412294, 1e-6);
+        assert_diff(result.velocity.x, 4.741887409, 1e-9);
+        assert_diff(result.velocity.y, -4.151817765, 1e-9);
+        assert_diff(result.velocity.z, -2.093935425, 1e-9);
+
+        // The default stays WGS-84.
+        let mut wgs84 = crate::io::twoline2satrec(tle1, tle2).unwrap();
+        assert_eq!(wgs84.gravity_model, GravityModel::Wgs84);
+        let wgs84_result = sgp4(&mut wgs84, 360.0).unwrap();
+        assert!((wgs84_result.position.y - result.position.y).abs() > 1e-3);
+
+        // Re-initializing an initialized satrec starts again from the original elements.
+        let mut reinit = init_satrec_with_options(wgs84, &options).unwrap();
+        assert_eq!(reinit.no, satrec.no);
+        assert_eq!(reinit.a, satrec.a);
+        let reinit_result = sgp4(&mut reinit, 360.0).unwrap();
+        assert_eq!(reinit_result.position.x, result.position.x);
+
+        let options = InitOptions { gravity_model: GravityModel::Wgs72Old, ..InitOptions::default() };
+        let mut wgs72old = twoline2satrec_with_options(tle1, tle2, &options).unwrap();
+        let wgs72old_result = sgp4(&mut wgs72old, 360.0).unwrap();
+        assert_diff(wgs72old_result.position.x, result.position.x, 1e-5);
+        assert!(wgs72old_result.position.x != result.position.x);
+    }
+
+    #[test]
+    fn test_opsmode() {
+        use crate::io::{twoline2satrec_with_options, InitOptions};
+        use crate::propogation::dpper::DpperOpsMode;
+        use crate::propogation::sgp4::sgp4;
+
+        let tle1 = "1 11801U          80230.29629788  .01431103  00000-0  14311-1        ";
+        let tle2 = "2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848      ";
+
+        let improved = crate::io::twoline2satrec(tle1, tle2).unwrap();
+        assert_eq!(improved.operationmode, DpperOpsMode::I);
+        assert_eq!(improved.gsto, 1.265125075734467);
+
+        let options = InitOptions { opsmode: DpperOpsMode::A, ..InitOptions::default() };
+        let afspc = twoline2satrec_with_options(tle1, tle2, &options).unwrap();
+        assert_eq!(afspc.operationmode, DpperOpsMode::A);
+        assert_eq!(afspc.gsto, 1.2651250757338985);
+
+        // Near earth orbits do not use the sidereal time or the lyddane modification.
+        let tle1 = "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0    8";
+        let tle2 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518  105";
+        let mut improved = crate::io::twoline2satrec(tle1, tle2).unwrap();
+        let mut afspc = twoline2satrec_with_options(tle1, tle2, &options).unwrap();
+        assert_eq!(
+            sgp4(&mut afspc, 360.0).unwrap().position.x,
+            sgp4(&mut improved, 360.0).unwrap().position.x
+        );
+    }
 }
@@ -0,0 +1,549 @@
+//! Parsing of CCSDS Orbit Mean-Elements Messages (OMM).
+//!
+//! OMM documents carry the same mean elements as a TLE, but without the fixed
+//! column layout or the five digit catalog number limit. This module accepts the
+//! XML, JSON and KVN (keyword = value) encodings published by Celestrak and
+//! Space-Track, and initializes a `Satrec` for each object through the same
+//! `sgp4init` path used by `twoline2satrec`.
+
+use std::collections::HashMap;
+
+use chrono::prelude::*;
+
+use crate::constants::*;
+use crate::io::cospar::InternationalDesignator;
+use crate::io::satnum::CatalogNumber;
+use crate::io::{day_of_year_to_jd, init_satrec, Satrec, SatrecParseError};
+
+/// Raw OMM keywords for a single object, keyed by their CCSDS names (e.g. `MEAN_MOTION`).
+type OmmFields = HashMap<String, String>;
+
+/// Parse an OMM document, detecting whether it is XML, JSON or KVN.
+///
+/// Returns every object that could be initialized along with the errors for those that
+/// could not. Per-object errors are wrapped in `SatrecParseError::SatrecMultiError` with
+/// the zero-based index of the object within the document.
+pub fn parse(string: &str) -> (Vec<Satrec>, Vec<SatrecParseError>) {
+    match string.trim_start().chars().next() {
+        Some('<') => parse_xml(string),
+        Some('[') | Some('{') => parse_json(string),
+        _ => parse_kvn(string),
+    }
+}
+
+/// Parse an OMM XML document containing either a single `<omm>` or an `<ndm>` with many.
+pub fn parse_xml(string: &str) -> (Vec<Satrec>, Vec<SatrecParseError>) {
+    match xml_fields(string) {
+        Ok(objects) => build_all(objects),
+        Err(err) => (vec![], vec![err]),
+    }
+}
+
+/// Parse an OMM JSON document: either a single object or an array of objects.
+pub fn parse_json(string: &str) -> (Vec<Satrec>, Vec<SatrecParseError>) {
+    match json_fields(string) {
+        Ok(objects) => build_all(objects),
+        Err(err) => (vec![], vec![err]),
+    }
+}
+
+/// Parse an OMM KVN document. Multiple messages may be concatenated, each starting with
+/// a `CCSDS_OMM_VERS` line.
+pub fn parse_kvn(string: &str) -> (Vec<Satrec>, Vec<SatrecParseError>) {
+    build_all(kvn_fields(string))
+}
+
+fn build_all(objects: Vec<OmmFields>) -> (Vec<Satrec>, Vec<SatrecParseError>) {
+    let mut recs: Vec<Satrec> = vec![];
+    let mut errors: Vec<SatrecParseError> = vec![];
+
+    for (i, fields) in objects.iter().enumerate() {
+        match fields_to_satrec(fields) {
+            Ok(rec) => recs.push(rec),
+            Err(err) => errors.push(SatrecParseError::SatrecMultiError(i, Box::new(err))),
+        }
+    }
+
+    (recs, errors)
+}
+
+fn xml_fields(string: &str) -> Result<Vec<OmmFields>, SatrecParseError> {
+    let doc = match roxmltree::Document::parse(string) {
+        Ok(doc) => doc,
+        Err(err) => return Err(SatrecParseError::OmmSyntaxError(err.to_string())),
+    };
+
+    let objects = doc
+        .descendants()
+        .filter(|node| node.is_element() && node.tag_name().name().eq_ignore_ascii_case("omm"))
+        .map(|omm| {
+            // Keywords are the leaf elements, regardless of which block they sit in.
+            omm.descendants()
+                .filter(|node| node.is_element() && !node.children().any(|c| c.is_element()))
+                .map(|node| {
+                    (
+                        node.tag_name().name().to_uppercase(),
+                        node.text().unwrap_or("").trim().to_string(),
+                    )
+                })
+                .collect::<OmmFields>()
+        })
+        .collect::<Vec<OmmFields>>();
+
+    if objects.is_empty() {
+        return Err(SatrecParseError::OmmSyntaxError(
+            "no <omm> element found".to_string(),
+        ));
+    }
+
+    Ok(objects)
+}
+
+fn json_fields(string: &str) -> Result<Vec<OmmFields>, SatrecParseError> {
+    let value: serde_json::Value = match serde_json::from_str(string) {
+        Ok(value) => value,
+        Err(err) => return Err(SatrecParseError::OmmSyntaxError(err.to_string())),
+    };
+
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        object @ serde_json::Value::Object(_) => vec![object],
+        _ => {
+            return Err(SatrecParseError::OmmSyntaxError(
+                "expected an object or an array of objects".to_string(),
+            ))
+        }
+    };
+
+    items
+        .into_iter()
+        .map(|item| match item {
+            serde_json::Value::Object(map) => Ok(map
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    // Space-Track quotes every value, Celestrak uses JSON numbers.
+                    let value = match value {
+                        serde_json::Value::String(s) => s,
+                        serde_json::Value::Number(n) => n.to_string(),
+                        serde_json::Value::Bool(b) => b.to_string(),
+                        _ => return None,
+                    };
+                    Some((key.to_uppercase(), value.trim().to_string()))
+                })
+                .collect::<OmmFields>()),
+            _ => Err(SatrecParseError::OmmSyntaxError(
+                "expected an array of objects".to_string(),
+            )),
+        })
+        .collect()
+}
+
+fn kvn_fields(string: &str) -> Vec<OmmFields> {
+    let mut objects: Vec<OmmFields> = vec![];
+    let mut current = OmmFields::new();
+
+    for line in string.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("COMMENT") {
+            continue;
+        }
+
+        let (key, value) = match line.find('=') {
+            Some(idx) => (line[..idx].trim().to_uppercase(), line[idx + 1..].trim()),
+            None => continue,
+        };
+
+        if key == "CCSDS_OMM_VERS" && !current.is_empty() {
+            objects.push(current);
+            current = OmmFields::new();
+        }
+
+        // Strip an optional trailing unit annotation, e.g. "51.6416 [deg]".
+        let value = match value.find('[') {
+            Some(idx) => value[..idx].trim(),
+            None => value,
+        };
+
+        current.insert(key, value.to_string());
+    }
+
+    if !current.is_empty() {
+        objects.push(current);
+    }
+
+    objects
+}
+
+fn field<'a>(fields: &'a OmmFields, name: &'static str) -> Result<&'a str, SatrecParseError> {
+    match fields.get(name) {
+        Some(value) if !value.is_empty() => Ok(value),
+        _ => Err(SatrecParseError::OmmMissingField(name)),
+    }
+}
+
+fn float_field(fields: &OmmFields, name: &'static str) -> Result<f64, SatrecParseError> {
+    let value = field(fields, name)?;
+    match value.parse::<f64>() {
+        Ok(res) => Ok(res),
+        Err(_) => Err(SatrecParseError::OmmInvalidField(name, value.to_string())),
+    }
+}
+
+fn optional_float_field(fields: &OmmFields, name: &'static str) -> Result<f64, SatrecParseError> {
+    match fields.get(name) {
+        Some(value) if !value.is_empty() => float_field(fields, name),
+        _ => Ok(0.0),
+    }
+}
+
+fn optional_int_field(fields: &OmmFields, name: &'static str) -> Result<i64, SatrecParseError> {
+    match fields.get(name) {
+        Some(value) if !value.is_empty() => match value.parse::<i64>() {
+            Ok(res) => Ok(res),
+            Err(_) => Err(SatrecParseError::OmmInvalidField(name, value.to_string())),
+        },
+        _ => Ok(0),
+    }
+}
+
+/// Parse a CCSDS epoch (`YYYY-MM-DDThh:mm:ss.ffff` or `YYYY-DDDThh:mm:ss.ffff`) into a
+/// four digit year and fractional day of year.
+fn parse_epoch(epoch: &str) -> Option<(i32, f64)> {
+    let epoch = epoch.trim().trim_end_matches('Z');
+    let mut parts = epoch.splitn(2, 'T');
+    let date = parts.next()?;
+    let time = parts.next().unwrap_or("00:00:00");
+
+    let date_parts = date.split('-').collect::<Vec<&str>>();
+    let (year, ordinal) = match date_parts.len() {
+        3 => {
+            let year = date_parts[0].parse::<i32>().ok()?;
+            let month = date_parts[1].parse::<u32>().ok()?;
+            let day = date_parts[2].parse::<u32>().ok()?;
+            (year, NaiveDate::from_ymd_opt(year, month, day)?.ordinal())
+        }
+        2 => {
+            let year = date_parts[0].parse::<i32>().ok()?;
+            let ordinal = date_parts[1].parse::<u32>().ok()?;
+            (year, NaiveDate::from_yo_opt(year, ordinal)?.ordinal())
+        }
+        _ => return None,
+    };
+
+    let time_parts = time.split(':').collect::<Vec<&str>>();
+    if time_parts.len() != 3 {
+        return None;
+    }
+    let hour = time_parts[0].parse::<f64>().ok()?;
+    let minute = time_parts[1].parse::<f64>().ok()?;
+    let second = time_parts[2].parse::<f64>().ok()?;
+
+    let day_fraction = ((hour * 3600.0) + (minute * 60.0) + second) / 86400.0;
+
+    Some((year, ordinal as f64 + day_fraction))
+}
+
+fn fields_to_satrec(fields: &OmmFields) -> Result<Satrec, SatrecParseError> {
+    if let Some(theory) = fields.get("MEAN_ELEMENT_THEORY") {
+        if !theory.is_empty() && !theory.eq_ignore_ascii_case("SGP4") {
+            return Err(SatrecParseError::OmmUnsupportedTheory(theory.clone()));
+        }
+    }
+
+    // OMM carries the full integer catalog number, not the Alpha-5 TLE encoding.
+    let satnum_str = field(fields, "NORAD_CAT_ID")?;
+    let satnum = match satnum_str.parse::<u32>() {
+        Ok(res) => CatalogNumber(res),
+        Err(_) => {
+            return Err(SatrecParseError::OmmInvalidField(
+                "NORAD_CAT_ID",
+                satnum_str.to_string(),
+            ))
+        }
+    };
+
+    let epoch_str = field(fields, "EPOCH")?;
+    let (year, epochdays) = match parse_epoch(epoch_str) {
+        Some(res) => res,
+        None => {
+            return Err(SatrecParseError::OmmInvalidField(
+                "EPOCH",
+                epoch_str.to_string(),
+            ))
+        }
+    };
+    // The two-digit year is only kept for TLE output; the epoch itself uses all four.
+    let epochyr = (year % 100) as i64;
+
+    let no = float_field(fields, "MEAN_MOTION")? / XPDOTP;
+    let ecco = float_field(fields, "ECCENTRICITY")?;
+    let inclo = float_field(fields, "INCLINATION")? * DEG_2_RAD;
+    let nodeo = float_field(fields, "RA_OF_ASC_NODE")? * DEG_2_RAD;
+    let argpo = float_field(fields, "ARG_OF_PERICENTER")? * DEG_2_RAD;
+    let mo = float_field(fields, "MEAN_ANOMALY")? * DEG_2_RAD;
+    let bstar = optional_float_field(fields, "BSTAR")?;
+    let ndot = optional_float_field(fields, "MEAN_MOTION_DOT")? / (XPDOTP * 1440.0);
+    let nddot = optional_float_field(fields, "MEAN_MOTION_DDOT")? / (XPDOTP * 1440.0 * 1440.0);
+
+    let classification = match fields.get("CLASSIFICATION_TYPE") {
+        Some(value) if !value.is_empty() => value.chars().next().unwrap_or('U'),
+        _ => 'U',
+    };
+
+    // Analyst objects have no designator, which some sources spell out as UNKNOWN.
+    let intldesg = match fields.get("OBJECT_ID") {
+        Some(value) if !value.is_empty() && value != "UNKNOWN" => {
+            Some(InternationalDesignator::from_omm(value)?)
+        }
+        _ => None,
+    };
+
+    let a = (no * TUMIN).powf(-2.0 / 3.0);
+
+    let mut satrec = Satrec::zero();
+
+    satrec.name = fields.get("OBJECT_NAME").cloned();
+    satrec.satnum = satnum;
+    satrec.classification = classification;
+    satrec.intldesg = intldesg;
+    satrec.ephtype = optional_int_field(fields, "EPHEMERIS_TYPE")?;
+    satrec.elnum = optional_int_field(fields, "ELEMENT_SET_NO")?;
+    satrec.revnum = optional_int_field(fields, "REV_AT_EPOCH")?;
+    satrec.epochyr = epochyr;
+    satrec.epochdays = epochdays;
+    satrec.ndot = ndot;
+    satrec.nddot = nddot;
+    satrec.bstar = bstar;
+    satrec.inclo = inclo;
+    satrec.nodeo = nodeo;
+    satrec.ecco = ecco;
+    satrec.argpo = argpo;
+    satrec.mo = mo;
+    satrec.no = no;
+    satrec.no_kozai = no;
+    satrec.a = a;
+    satrec.alta = (a * (1.0 + ecco)) - 1.0;
+    satrec.altp = (a * (1.0 - ecco)) - 1.0;
+    satrec.jdsatepoch = day_of_year_to_jd(year as i64, epochdays);
+
+    init_satrec(satrec)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::omm::*;
+    use crate::tests::assert_diff;
+
+    const ISS_TLE1: &str = "1 25544U 98067A   19085.83761025  .00001292  00000-0  28282-4 0  9995";
+    const ISS_TLE2: &str = "2 25544  51.6446  50.5941 0002332 117.0184 328.1109 15.52438493162461";
+
+    const ISS_XML: &str = r###"<?xml version="1.0" encoding="UTF-8"?>
+<ndm xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+<omm id="CCSDS_OMM_VERS" version="2.0">
+<header><CREATION_DATE/><ORIGINATOR/></header>
+<body><segment>
+<metadata>
+<OBJECT_NAME>ISS (ZARYA)</OBJECT_NAME>
+<OBJECT_ID>1998-067A</OBJECT_ID>
+<CENTER_NAME>EARTH</CENTER_NAME>
+<REF_FRAME>TEME</REF_FRAME>
+<TIME_SYSTEM>UTC</TIME_SYSTEM>
+<MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY>
+</metadata>
+<data>
+<meanElements>
+<EPOCH>2019-03-26T20:06:09.525600</EPOCH>
+<MEAN_MOTION>15.52438493</MEAN_MOTION>
+<ECCENTRICITY>.0002332</ECCENTRICITY>
+<INCLINATION>51.6446</INCLINATION>
+<RA_OF_ASC_NODE>50.5941</RA_OF_ASC_NODE>
+<ARG_OF_PERICENTER>117.0184</ARG_OF_PERICENTER>
+<MEAN_ANOMALY>328.1109</MEAN_ANOMALY>
+</meanElements>
+<tleParameters>
+<EPHEMERIS_TYPE>0</EPHEMERIS_TYPE>
+<CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE>
+<NORAD_CAT_ID>25544</NORAD_CAT_ID>
+<ELEMENT_SET_NO>999</ELEMENT_SET_NO>
+<REV_AT_EPOCH>16246</REV_AT_EPOCH>
+<BSTAR>.28282E-4</BSTAR>
+<MEAN_MOTION_DOT>.00001292</MEAN_MOTION_DOT>
+<MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT>
+</tleParameters>
+</data>
+</segment></body>
+</omm>
+</ndm>"###;
+
+    const ISS_JSON: &str = r###"[{
+        "OBJECT_NAME": "ISS (ZARYA)",
+        "OBJECT_ID": "1998-067A",
+        "EPOCH": "2019-03-26T20:06:09.525600",
+        "MEAN_MOTION": 15.52438493,
+        "ECCENTRICITY": 0.0002332,
+        "INCLINATION": 51.6446,
+        "RA_OF_ASC_NODE": 50.5941,
+        "ARG_OF_PERICENTER": 117.0184,
+        "MEAN_ANOMALY": 328.1109,
+        "EPHEMERIS_TYPE": 0,
+        "CLASSIFICATION_TYPE": "U",
+        "NORAD_CAT_ID": 25544,
+        "ELEMENT_SET_NO": 999,
+        "REV_AT_EPOCH": 16246,
+        "BSTAR": 2.8282e-5,
+        "MEAN_MOTION_DOT": "0.00001292",
+        "MEAN_MOTION_DDOT": 0
+    }]"###;
+
+    const ISS_KVN: &str = r###"CCSDS_OMM_VERS = 2.0
+COMMENT generated for tests
+CREATION_DATE = 2019-03-27T00:00:00
+ORIGINATOR = TEST
+OBJECT_NAME = ISS (ZARYA)
+OBJECT_ID = 1998-067A
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+TIME_SYSTEM = UTC
+MEAN_ELEMENT_THEORY = SGP4
+EPOCH = 2019-085T20:06:09.525600
+MEAN_MOTION = 15.52438493 [rev/day]
+ECCENTRICITY = .0002332
+INCLINATION = 51.6446 [deg]
+RA_OF_ASC_NODE = 50.5941 [deg]
+ARG_OF_PERICENTER = 117.0184 [deg]
+MEAN_ANOMALY = 328.1109 [deg]
+EPHEMERIS_TYPE = 0
+CLASSIFICATION_TYPE = U
+NORAD_CAT_ID = 25544
+ELEMENT_SET_NO = 999
+REV_AT_EPOCH = 16246
+BSTAR = .28282E-4
+MEAN_MOTION_DOT = .00001292
+MEAN_MOTION_DDOT = 0
+"###;
+
+    fn assert_matches_tle(sat: &Satrec) {
+        let tle = crate::io::twoline2satrec(ISS_TLE1, ISS_TLE2).unwrap();
+
+        assert_eq!(sat.name, Some("ISS (ZARYA)".to_string()));
+        assert_eq!(sat.satnum, tle.satnum);
+        assert_eq!(sat.classification, 'U');
+        assert_eq!(sat.intldesg, tle.intldesg);
+        assert_eq!(sat.ephtype, tle.ephtype);
+        assert_eq!(sat.elnum, tle.elnum);
+        assert_eq!(sat.revnum, tle.revnum);
+        assert_eq!(sat.epochyr, tle.epochyr);
+        assert_diff(sat.epochdays, tle.epochdays, 1e-9);
+        assert_diff(sat.jdsatepoch, tle.jdsatepoch, 1e-8);
+        assert_eq!(sat.ndot, tle.ndot);
+        assert_eq!(sat.nddot, tle.nddot);
+        assert_eq!(sat.bstar, tle.bstar);
+        assert_eq!(sat.inclo, tle.inclo);
+        assert_eq!(sat.nodeo, tle.nodeo);
+        assert_eq!(sat.ecco, tle.ecco);
+        assert_eq!(sat.argpo, tle.argpo);
+        assert_eq!(sat.mo, tle.mo);
+        assert_eq!(sat.no, tle.no);
+        assert_eq!(sat.method, tle.method);
+    }
+
+    #[test]
+    fn test_parse_xml() {
+        let (sats, errors) = parse_xml(ISS_XML);
+        assert_eq!(errors, vec![]);
+        assert_eq!(sats.len(), 1);
+        assert_matches_tle(&sats[0]);
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let (sats, errors) = parse_json(ISS_JSON);
+        assert_eq!(errors, vec![]);
+        assert_eq!(sats.len(), 1);
+        assert_matches_tle(&sats[0]);
+    }
+
+    #[test]
+    fn test_parse_kvn() {
+        let (sats, errors) = parse_kvn(ISS_KVN);
+        assert_eq!(errors, vec![]);
+        assert_eq!(sats.len(), 1);
+        assert_matches_tle(&sats[0]);
+    }
+
+    #[test]
+    fn test_epoch_outside_tle_years() {
+        // the TLE two-digit years cover 1957 to 2056 only.
+        for &year in &[2063, 1956] {
+            let kvn = ISS_KVN.replace("2019-085", &format!("{}-085", year));
+            let (sats, errors) = parse_kvn(&kvn);
+            assert_eq!(errors, vec![]);
+
+            let expected = NaiveDate::from_yo_opt(year, 85)
+                .unwrap()
+                .and_hms_micro_opt(20, 6, 9, 525600)
+                .unwrap()
+                .and_utc();
+            let offset = (sats[0].epoch() - expected).num_microseconds().unwrap();
+
+            assert_eq!(sats[0].epochyr, i64::from(year % 100));
+            assert!(offset.abs() <= 20, "{}: {} us", year, offset);
+        }
+    }
+
+    #[test]
+    fn test_parse_detect() {
+        assert_eq!(parse(ISS_XML).0.len(), 1);
+        assert_eq!(parse(ISS_JSON).0.len(), 1);
+        assert_eq!(parse(ISS_KVN).0.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_multiple_with_errors() {
+        let doc = format!(
+            "{}\n{}\n{}",
+            ISS_KVN,
+            ISS_KVN.replace("MEAN_MOTION = 15.52438493 [rev/day]\n", ""),
+            ISS_KVN.replace("NORAD_CAT_ID = 25544", "NORAD_CAT_ID = 270000"),
+        );
+
+        let (sats, errors) = parse_kvn(&doc);
+        assert_eq!(sats.len(), 2);
+        assert_eq!(sats[1].satnum, CatalogNumber(270000));
+        assert_eq!(
+            errors,
+            vec![SatrecParseError::SatrecMultiError(
+                1,
+                Box::new(SatrecParseError::OmmMissingField("MEAN_MOTION"))
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let (sats, errors) = parse_json("[{\"NORAD_CAT_ID\": 1, \"EPOCH\": \"yesterday\"}]");
+        assert_eq!(sats.len(), 0);
+        assert_eq!(
+            errors,
+            vec![SatrecParseError::SatrecMultiError(
+                0,
+                Box::new(SatrecParseError::OmmInvalidField(
+                    "EPOCH",
+                    "yesterday".to_string()
+                ))
+            )]
+        );
+
+        let (_, errors) = parse_xml("<omm><unclosed></omm>");
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse_kvn(&ISS_KVN.replace("SGP4", "SGP4-XP"));
+        assert_eq!(
+            errors,
+            vec![SatrecParseError::SatrecMultiError(
+                0,
+                Box::new(SatrecParseError::OmmUnsupportedTheory("SGP4-XP".to_string()))
+            )]
+        );
+    }
+}
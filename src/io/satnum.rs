@@ -0,0 +1,126 @@
+//! Satellite catalog numbers, including the Alpha-5 TLE encoding.
+//!
+//! TLEs only reserve five columns for the catalog number. Alpha-5 extends the range to
+//! 339999 by replacing the leading digit with a letter (`A` = 10 ... `Z` = 33, skipping
+//! `I` and `O` to avoid confusion with `1` and `0`). OMM documents carry the full
+//! integer directly.
+
+use std::fmt;
+
+use crate::io::SatrecParseError;
+
+/// Letters used for the leading Alpha-5 character, in order of value starting at 10.
+const ALPHA5_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// Largest catalog number that can be written in a TLE (`Z9999`).
+pub const MAX_ALPHA5: u32 = 339_999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+/// NORAD satellite catalog number.
+pub struct CatalogNumber(pub u32);
+
+impl CatalogNumber {
+    /// Decode the five catalog number columns of a TLE, either all digits or Alpha-5.
+    pub fn from_alpha5(text: &str) -> Result<CatalogNumber, SatrecParseError> {
+        let trimmed = text.trim();
+        let invalid = || SatrecParseError::InvalidCatalogNumber(text.to_string());
+
+        let first = match trimmed.bytes().next() {
+            Some(c) => c,
+            None => return Err(invalid()),
+        };
+
+        if trimmed.len() > 5 || !trimmed.bytes().skip(1).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        if first.is_ascii_digit() {
+            return trimmed.parse::<u32>().map(CatalogNumber).map_err(|_| invalid());
+        }
+
+        // A leading letter is only meaningful with exactly four digits after it.
+        if trimmed.len() != 5 {
+            return Err(invalid());
+        }
+
+        let high = match ALPHA5_LETTERS.iter().position(|&c| c == first) {
+            Some(idx) => idx as u32 + 10,
+            None => return Err(invalid()),
+        };
+
+        let low = trimmed[1..].parse::<u32>().map_err(|_| invalid())?;
+
+        Ok(CatalogNumber((high * 10_000) + low))
+    }
+
+    /// Encode for the five TLE columns, zero padded, using Alpha-5 above 99999.
+    ///
+    /// Returns `None` if the number is larger than `MAX_ALPHA5`.
+    pub fn to_alpha5(self) -> Option<String> {
+        match self.0 {
+            0..=99_999 => Some(format!("{:05}", self.0)),
+            100_000..=MAX_ALPHA5 => {
+                let letter = ALPHA5_LETTERS[(self.0 / 10_000) as usize - 10] as char;
+                Some(format!("{}{:04}", letter, self.0 % 10_000))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CatalogNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for CatalogNumber {
+    fn from(number: u32) -> CatalogNumber {
+        CatalogNumber(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::satnum::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(CatalogNumber::from_alpha5("25544"), Ok(CatalogNumber(25544)));
+        assert_eq!(CatalogNumber::from_alpha5("00005"), Ok(CatalogNumber(5)));
+        assert_eq!(CatalogNumber::from_alpha5("    5"), Ok(CatalogNumber(5)));
+        assert_eq!(CatalogNumber::from_alpha5("A0000"), Ok(CatalogNumber(100000)));
+        assert_eq!(CatalogNumber::from_alpha5("E8493"), Ok(CatalogNumber(148493)));
+        assert_eq!(CatalogNumber::from_alpha5("J2931"), Ok(CatalogNumber(182931)));
+        assert_eq!(CatalogNumber::from_alpha5("P0001"), Ok(CatalogNumber(230001)));
+        assert_eq!(CatalogNumber::from_alpha5("Z9999"), Ok(CatalogNumber(339999)));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        for text in &["I0000", "O1234", "a0000", "A123", "", "1234X", "123456"] {
+            assert_eq!(
+                CatalogNumber::from_alpha5(text),
+                Err(SatrecParseError::InvalidCatalogNumber(text.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(CatalogNumber(5).to_alpha5(), Some("00005".to_string()));
+        assert_eq!(CatalogNumber(99999).to_alpha5(), Some("99999".to_string()));
+        assert_eq!(CatalogNumber(100000).to_alpha5(), Some("A0000".to_string()));
+        assert_eq!(CatalogNumber(182931).to_alpha5(), Some("J2931".to_string()));
+        assert_eq!(CatalogNumber(339999).to_alpha5(), Some("Z9999".to_string()));
+        assert_eq!(CatalogNumber(340000).to_alpha5(), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for number in (0..=MAX_ALPHA5).step_by(997) {
+            let encoded = CatalogNumber(number).to_alpha5().unwrap();
+            assert_eq!(CatalogNumber::from_alpha5(&encoded), Ok(CatalogNumber(number)));
+        }
+    }
+}
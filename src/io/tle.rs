@@ -0,0 +1,652 @@
+//! Validation of two-line element sets, and serialization of a `Satrec` back into one.
+
+use crate::constants::*;
+use crate::io::cospar::InternationalDesignator;
+use crate::io::satnum::CatalogNumber;
+use crate::io::{Satrec, SatrecParseError};
+
+#[derive(Debug, PartialEq)]
+pub enum TleWriteError {
+    /// The value does not fit in the fixed-width TLE column for the named field.
+    FieldOutOfRange(&'static str, f64),
+    /// The catalog number is too large for the five TLE columns, even as Alpha-5.
+    SatnumOutOfRange(CatalogNumber),
+    /// The international designator does not fit in the eight TLE columns.
+    DesignatorOutOfRange(InternationalDesignator),
+}
+
+/// Compute the modulo-10 checksum of a TLE line.
+///
+/// Digits count for their value, minus signs count as one, and everything else is
+/// ignored. Only the first 68 columns are considered, so a line that already carries
+/// a checksum in column 69 may be passed directly.
+pub fn checksum(line: &str) -> u8 {
+    let sum: u32 = line
+        .bytes()
+        .take(68)
+        .map(|c| match c {
+            b'0'..=b'9' => (c - b'0') as u32,
+            b'-' => 1,
+            _ => 0,
+        })
+        .sum();
+
+    (sum % 10) as u8
+}
+
+/// Expected layout of a TLE field: first and last column (1-based), name and pattern.
+///
+/// Pattern characters: `d` digit, `_` digit or blank, `s` sign (`+`, `-` or blank),
+/// `a` uppercase letter or blank, `x` digit or Alpha-5 letter, `c` classification (`U`, `C`, `S` or blank); anything
+/// else must match literally.
+type TleField = (usize, usize, &'static str, &'static str);
+
+const LINE1_FIELDS: &[TleField] = &[
+    (1, 1, "line number", "1"),
+    (2, 2, "separator", " "),
+    (3, 7, "catalog number", "xdddd"),
+    (8, 8, "classification", "c"),
+    (9, 9, "separator", " "),
+    (10, 17, "international designator", "_____aaa"),
+    (18, 18, "separator", " "),
+    (19, 32, "epoch", "dd___.dddddddd"),
+    (33, 33, "separator", " "),
+    (34, 43, "first derivative of mean motion", "s.dddddddd"),
+    (44, 44, "separator", " "),
+    (45, 52, "second derivative of mean motion", "sdddddsd"),
+    (53, 53, "separator", " "),
+    (54, 61, "bstar", "sdddddsd"),
+    (62, 62, "separator", " "),
+    (63, 63, "ephemeris type", "_"),
+    (64, 64, "separator", " "),
+    (65, 68, "element set number", "____"),
+    (69, 69, "checksum", "d"),
+];
+
+const LINE2_FIELDS: &[TleField] = &[
+    (1, 1, "line number", "2"),
+    (2, 2, "separator", " "),
+    (3, 7, "catalog number", "xdddd"),
+    (8, 8, "separator", " "),
+    (9, 16, "inclination", "___.dddd"),
+    (17, 17, "separator", " "),
+    (18, 25, "right ascension of ascending node", "___.dddd"),
+    (26, 26, "separator", " "),
+    (27, 33, "eccentricity", "ddddddd"),
+    (34, 34, "separator", " "),
+    (35, 42, "argument of perigee", "___.dddd"),
+    (43, 43, "separator", " "),
+    (44, 51, "mean anomaly", "___.dddd"),
+    (52, 52, "separator", " "),
+    (53, 63, "mean motion", "__.dddddddd"),
+    (64, 68, "revolution number", "_____"),
+    (69, 69, "checksum", "d"),
+];
+
+fn matches_pattern(text: &str, pattern: &str) -> bool {
+    text.len() == pattern.len()
+        && text.bytes().zip(pattern.bytes()).all(|(c, p)| match p {
+            b'd' => c.is_ascii_digit(),
+            b'_' => c.is_ascii_digit() || c == b' ',
+            b's' => c == b' ' || c == b'+' || c == b'-',
+            b'a' => c.is_ascii_uppercase() || c == b' ',
+            b'x' => c.is_ascii_digit() || (c.is_ascii_uppercase() && c != b'I' && c != b'O'),
+            b'c' => c == b'U' || c == b'C' || c == b'S' || c == b' ',
+            _ => c == p,
+        })
+}
+
+/// Check that a line is ASCII, so that it can be sliced by column.
+pub(crate) fn check_ascii(line: &str, line_number: usize) -> Result<(), SatrecParseError> {
+    match line.char_indices().find(|(_, c)| !c.is_ascii()) {
+        Some((idx, c)) => {
+            let column = line[..idx].chars().count() + 1;
+            Err(SatrecParseError::InvalidTLEField(
+                line_number,
+                column,
+                column,
+                "ASCII character".to_string(),
+                c.to_string(),
+            ))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Check that a line has at least `min` columns and, if given, at most `max`.
+pub(crate) fn check_length(
+    line: &str,
+    line_number: usize,
+    min: usize,
+    max: Option<usize>,
+) -> Result<(), SatrecParseError> {
+    let (first, last) = match max {
+        _ if line.len() < min => (line.len() + 1, min),
+        Some(max) if line.len() > max => (max + 1, line.len()),
+        _ => return Ok(()),
+    };
+
+    let expected = match max {
+        Some(max) if max == min => format!("{} columns", min),
+        Some(max) => format!("{} to {} columns", min, max),
+        None => format!("at least {} columns", min),
+    };
+
+    Err(SatrecParseError::InvalidTLEField(
+        line_number,
+        first,
+        last,
+        expected,
+        format!("{} columns", line.len()),
+    ))
+}
+
+fn validate_line(
+    line: &str,
+    line_number: usize,
+    fields: &[TleField],
+    errors: &mut Vec<SatrecParseError>,
+) -> bool {
+    if let Err(err) = check_ascii(line, line_number) {
+        errors.push(err);
+        return false;
+    }
+
+    if let Err(err) = check_length(line, line_number, 69, Some(69)) {
+        errors.push(err);
+    }
+
+    for &(first, last, name, pattern) in fields {
+        let text = match line.get(first - 1..last) {
+            Some(text) => text,
+            None => break,
+        };
+
+        if !matches_pattern(text, pattern) {
+            errors.push(SatrecParseError::InvalidTLEField(
+                line_number,
+                first,
+                last,
+                format!("{} '{}'", name, pattern),
+                text.to_string(),
+            ));
+        }
+    }
+
+    if let Some(found) = line.get(68..69) {
+        let expected = checksum(line).to_string();
+        if found != expected && matches_pattern(found, "d") {
+            errors.push(SatrecParseError::InvalidTLEField(
+                line_number,
+                69,
+                69,
+                format!("checksum '{}'", expected),
+                found.to_string(),
+            ));
+        }
+    }
+
+    true
+}
+
+/// Check the structure of a TLE, returning every problem found.
+///
+/// Both lines must be 69 ASCII columns with the right line numbers, the same catalog
+/// number, the expected character class in every column and a valid checksum. Each
+/// problem is reported as `SatrecParseError::InvalidTLEField`. Trailing whitespace
+/// (including a carriage return) is ignored.
+pub fn validate_tle(str1: &str, str2: &str) -> Vec<SatrecParseError> {
+    let line1 = str1.trim_end();
+    let line2 = str2.trim_end();
+    let mut errors: Vec<SatrecParseError> = vec![];
+
+    let ascii1 = validate_line(line1, 1, LINE1_FIELDS, &mut errors);
+    let ascii2 = validate_line(line2, 2, LINE2_FIELDS, &mut errors);
+
+    if ascii1 && ascii2 {
+        if let (Some(satnum1), Some(satnum2)) = (line1.get(2..7), line2.get(2..7)) {
+            if satnum1 != satnum2 {
+                errors.push(SatrecParseError::InvalidTLEField(
+                    2,
+                    3,
+                    7,
+                    format!("catalog number '{}' from line 1", satnum1),
+                    satnum2.to_string(),
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+fn with_checksum(line: String) -> String {
+    let sum = checksum(&line);
+    format!("{}{}", line, sum)
+}
+
+/// Format a value as a sign followed by a decimal point and eight digits (" .00001292").
+fn format_decimal_point(value: f64, name: &'static str) -> Result<String, TleWriteError> {
+    let digits = format!("{:.8}", value.abs());
+    if !digits.starts_with("0.") {
+        return Err(TleWriteError::FieldOutOfRange(name, value));
+    }
+
+    let sign = if value < 0.0 && digits != "0.00000000" { '-' } else { ' ' };
+    Ok(format!("{}{}", sign, &digits[1..]))
+}
+
+/// Format a value in the implied-decimal notation, e.g. 0.28282e-4 as " 28282-4".
+fn format_implied_decimal(value: f64, name: &'static str) -> Result<String, TleWriteError> {
+    if value == 0.0 {
+        return Ok(" 00000+0".to_string());
+    }
+
+    // Rust's scientific formatting handles rounding carry for us: d.dddde<x> with the
+    // mantissa in [1, 10) is 0.ddddd x 10^(x + 1) in TLE terms.
+    let scientific = format!("{:.4e}", value.abs());
+    let mut parts = scientific.split('e');
+    let mantissa = parts.next().unwrap_or("").replace('.', "");
+    let exponent = parts.next().unwrap_or("").parse::<i32>().unwrap_or(0) + 1;
+
+    if exponent < -9 {
+        // Below the smallest representable magnitude.
+        return Ok(" 00000+0".to_string());
+    }
+
+    if exponent > 9 {
+        return Err(TleWriteError::FieldOutOfRange(name, value));
+    }
+
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+
+    Ok(format!("{}{}{}{}", sign, mantissa, exponent_sign, exponent.abs()))
+}
+
+/// Format an angle in radians as degrees in an 8 column field.
+fn format_angle(radians: f64, name: &'static str) -> Result<String, TleWriteError> {
+    let degrees = format!("{:8.4}", radians * RAD_TO_DEG);
+    if degrees.len() != 8 {
+        return Err(TleWriteError::FieldOutOfRange(name, radians));
+    }
+
+    Ok(degrees)
+}
+
+/// Write the two lines of a TLE for the given satrec.
+///
+/// The mean elements are written from the values as originally parsed (the Kozai mean
+/// motion, not the un-Kozai'd value `sgp4init` leaves in `no`), so parsing the result
+/// produces the same elements. Each line has its checksum appended in column 69.
+pub fn write_tle(satrec: &Satrec) -> Result<(String, String), TleWriteError> {
+    let satnum = match satrec.satnum.to_alpha5() {
+        Some(satnum) => satnum,
+        None => return Err(TleWriteError::SatnumOutOfRange(satrec.satnum)),
+    };
+
+    let epoch = format!("{:02}{:012.8}", satrec.epochyr, satrec.epochdays);
+    if epoch.len() != 14 {
+        return Err(TleWriteError::FieldOutOfRange("epochdays", satrec.epochdays));
+    }
+
+    let ndot = format_decimal_point(satrec.ndot * XPDOTP * 1440.0, "ndot")?;
+    let nddot = format_implied_decimal(satrec.nddot * XPDOTP * 1440.0 * 1440.0, "nddot")?;
+    let bstar = format_implied_decimal(satrec.bstar, "bstar")?;
+
+    let intldesg = match &satrec.intldesg {
+        Some(intldesg) if intldesg.to_tle().len() > 8 => {
+            return Err(TleWriteError::DesignatorOutOfRange(intldesg.clone()))
+        }
+        Some(intldesg) => intldesg.to_tle(),
+        None => String::new(),
+    };
+
+    if !(0..=9).contains(&satrec.ephtype) {
+        return Err(TleWriteError::FieldOutOfRange("ephtype", satrec.ephtype as f64));
+    }
+
+    if !(0..=9999).contains(&satrec.elnum) {
+        return Err(TleWriteError::FieldOutOfRange("elnum", satrec.elnum as f64));
+    }
+
+    let line1 = format!(
+        "1 {}{} {:<8} {} {} {} {} {} {:>4}",
+        satnum,
+        satrec.classification,
+        intldesg,
+        epoch,
+        ndot,
+        nddot,
+        bstar,
+        satrec.ephtype,
+        satrec.elnum
+    );
+
+    let inclo = format_angle(satrec.inclo, "inclo")?;
+    let nodeo = format_angle(satrec.nodeo, "nodeo")?;
+    let argpo = format_angle(satrec.argpo, "argpo")?;
+    let mo = format_angle(satrec.mo, "mo")?;
+
+    let ecco = format!("{:.7}", satrec.ecco);
+    if !ecco.starts_with("0.") {
+        return Err(TleWriteError::FieldOutOfRange("ecco", satrec.ecco));
+    }
+
+    let no = format!("{:11.8}", satrec.no_kozai * XPDOTP);
+    if no.len() != 11 {
+        return Err(TleWriteError::FieldOutOfRange("no", satrec.no_kozai));
+    }
+
+    let line2 = format!(
+        "2 {} {} {} {} {} {} {}{:>5}",
+        satnum,
+        inclo,
+        nodeo,
+        &ecco[2..],
+        argpo,
+        mo,
+        no,
+        // Revolution numbers conventionally wrap at 100000 in the five TLE columns.
+        satrec.revnum.rem_euclid(100_000)
+    );
+
+    Ok((with_checksum(line1), with_checksum(line2)))
+}
+
+/// Write a TLE, preceded by the satellite name if the satrec has one (3LE format).
+pub fn write_3le(satrec: &Satrec) -> Result<String, TleWriteError> {
+    let (line1, line2) = write_tle(satrec)?;
+
+    match &satrec.name {
+        Some(name) => Ok(format!("{}\n{}\n{}", name, line1, line2)),
+        None => Ok(format!("{}\n{}", line1, line2)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::tle::*;
+    use crate::io::SatrecParseError;
+    use crate::tests::verification_tles;
+
+    const ISS_TLE1: &str = "1 25544U 98067A   19085.83761025  .00001292  00000-0  28282-4 0  9995";
+    const ISS_TLE2: &str = "2 25544  51.6446  50.5941 0002332 117.0184 328.1109 15.52438493162461";
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(
+            checksum("1 25544U 98067A   19085.83761025  .00001292  00000-0  28282-4 0  9995"),
+            5
+        );
+        assert_eq!(
+            checksum("2 25544  51.6446  50.5941 0002332 117.0184 328.1109 15.52438493162461"),
+            1
+        );
+    }
+
+    #[test]
+    fn test_validate_valid() {
+        assert_eq!(validate_tle(ISS_TLE1, ISS_TLE2), vec![]);
+        assert_eq!(validate_tle(&format!("{}\r", ISS_TLE1), ISS_TLE2), vec![]);
+    }
+
+    #[test]
+    fn test_validate_catalog() {
+        use crate::io::{parse_satrec_validated, TleValidation};
+
+        for (tle1, tle2) in verification_tles() {
+            let errors = validate_tle(tle1, &tle2[..69]);
+            if tle1.starts_with("1 3333") {
+                assert!(!errors.is_empty(), "{}", tle1);
+            } else if tle1.starts_with("1 25954") {
+                // a real element set published with a bad line 2 checksum
+                let checksum_error = || {
+                    SatrecParseError::InvalidTLEField(
+                        2,
+                        69,
+                        69,
+                        "checksum '1'".to_string(),
+                        "7".to_string(),
+                    )
+                };
+                assert_eq!(errors, vec![checksum_error()]);
+
+                let line2 = &tle2[..69];
+                let (_, warnings) = parse_satrec_validated(tle1, line2, TleValidation::Lenient)
+                    .expect("lenient parsing reports the checksum as a warning");
+                assert_eq!(warnings, vec![checksum_error()]);
+                assert_eq!(
+                    parse_satrec_validated(tle1, line2, TleValidation::Strict).err(),
+                    Some(checksum_error())
+                );
+            } else {
+                assert_eq!(errors, vec![], "{}", tle1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_errors() {
+        assert_eq!(
+            validate_tle(&ISS_TLE1.replace("9995", "9994"), ISS_TLE2),
+            vec![SatrecParseError::InvalidTLEField(
+                1,
+                69,
+                69,
+                "checksum '5'".to_string(),
+                "4".to_string()
+            )]
+        );
+
+        assert_eq!(
+            validate_tle(ISS_TLE1, &ISS_TLE2.replacen("25544", "25545", 1)),
+            vec![
+                SatrecParseError::InvalidTLEField(
+                    2,
+                    69,
+                    69,
+                    "checksum '2'".to_string(),
+                    "1".to_string()
+                ),
+                SatrecParseError::InvalidTLEField(
+                    2,
+                    3,
+                    7,
+                    "catalog number '25544' from line 1".to_string(),
+                    "25545".to_string()
+                ),
+            ]
+        );
+
+        assert_eq!(
+            validate_tle(&ISS_TLE1.replace("19085.8", "19085,8"), ISS_TLE2)[0],
+            SatrecParseError::InvalidTLEField(
+                1,
+                19,
+                32,
+                "epoch 'dd___.dddddddd'".to_string(),
+                "19085,83761025".to_string()
+            )
+        );
+
+        assert_eq!(
+            validate_tle(&ISS_TLE1[..40], ISS_TLE2)[0],
+            SatrecParseError::InvalidTLEField(
+                1,
+                41,
+                69,
+                "69 columns".to_string(),
+                "40 columns".to_string()
+            )
+        );
+
+        assert_eq!(
+            validate_tle(ISS_TLE1, &ISS_TLE2.replace("51.6446", "51.6446°")),
+            vec![SatrecParseError::InvalidTLEField(
+                2,
+                17,
+                17,
+                "ASCII character".to_string(),
+                "°".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_malformed_does_not_panic() {
+        assert_eq!(
+            crate::io::parse_satrec(&ISS_TLE1[..40], ISS_TLE2).err(),
+            Some(SatrecParseError::InvalidTLEField(
+                1,
+                41,
+                61,
+                "at least 61 columns".to_string(),
+                "40 columns".to_string()
+            ))
+        );
+        assert!(crate::io::parse_satrec(ISS_TLE1, &ISS_TLE2.replace("0002332", "00023é")).is_err());
+        assert!(crate::io::parse("0\n1 2\n2 3").is_err());
+    }
+
+    #[test]
+    fn test_validation_modes() {
+        use crate::io::{twoline2satrec_validated, TleValidation};
+
+        let bad = ISS_TLE1.replace("9995", "9994");
+
+        assert!(twoline2satrec_validated(&bad, ISS_TLE2, TleValidation::Strict).is_err());
+
+        let (satrec, warnings) =
+            twoline2satrec_validated(&bad, ISS_TLE2, TleValidation::Lenient).unwrap();
+        assert_eq!(satrec.satnum, CatalogNumber(25544));
+        assert_eq!(warnings.len(), 1);
+
+        let (_, warnings) =
+            twoline2satrec_validated(ISS_TLE1, ISS_TLE2, TleValidation::Strict).unwrap();
+        assert_eq!(warnings, vec![]);
+    }
+
+    fn fix_checksum(line: &str) -> String {
+        format!("{}{}", &line[..68], checksum(line))
+    }
+
+    #[test]
+    fn test_alpha5() {
+        use crate::io::{twoline2satrec_validated, TleValidation};
+
+        let tle1 = fix_checksum(&ISS_TLE1.replace("25544", "E8493"));
+        let tle2 = fix_checksum(&ISS_TLE2.replace("25544", "E8493"));
+
+        let (satrec, _) = twoline2satrec_validated(&tle1, &tle2, TleValidation::Strict).unwrap();
+        assert_eq!(satrec.satnum, CatalogNumber(148493));
+
+        let (line1, line2) = write_tle(&satrec).unwrap();
+        assert_eq!(&line1[..17], "1 E8493U 98067A  ");
+        assert_eq!(&line2[..8], "2 E8493 ");
+
+        let mut satrec = satrec;
+        satrec.satnum = CatalogNumber(123456789);
+        assert_eq!(
+            write_tle(&satrec),
+            Err(TleWriteError::SatnumOutOfRange(CatalogNumber(123456789)))
+        );
+    }
+
+    #[test]
+    fn test_implied_decimal() {
+        assert_eq!(format_implied_decimal(0.28282e-4, "").unwrap(), " 28282-4");
+        assert_eq!(format_implied_decimal(-0.30915e-6, "").unwrap(), "-30915-6");
+        assert_eq!(format_implied_decimal(0.13519, "").unwrap(), " 13519+0");
+        assert_eq!(format_implied_decimal(0.0, "").unwrap(), " 00000+0");
+        assert_eq!(format_implied_decimal(0.999999e-3, "").unwrap(), " 10000-2");
+        assert_eq!(
+            format_implied_decimal(1e10, "bstar"),
+            Err(TleWriteError::FieldOutOfRange("bstar", 1e10))
+        );
+    }
+
+    #[test]
+    fn test_write_columns() {
+        let satrec = crate::io::twoline2satrec(
+            "1 25544U 98067A   19085.83761025  .00001292  00000-0  28282-4 0  9995",
+            "2 25544  51.6446  50.5941 0002332 117.0184 328.1109 15.52438493162461",
+        )
+        .unwrap();
+
+        let (line1, line2) = write_tle(&satrec).unwrap();
+
+        assert_eq!(line1.len(), 69);
+        assert_eq!(line2.len(), 69);
+        assert_eq!(&line1[..43], &ISS_TLE1[..43]);
+        assert_eq!(&line1[52..68], &ISS_TLE1[52..68]);
+        assert_eq!(&line2[..68], &ISS_TLE2[..68]);
+        assert_eq!(checksum(&line1), line1[68..].parse::<u8>().unwrap());
+        assert_eq!(checksum(&line2), line2[68..].parse::<u8>().unwrap());
+    }
+
+    #[test]
+    fn test_write_3le() {
+        let mut satrec = crate::io::twoline2satrec(
+            "1 25544U 98067A   19085.83761025  .00001292  00000-0  28282-4 0  9995",
+            "2 25544  51.6446  50.5941 0002332 117.0184 328.1109 15.52438493162461",
+        )
+        .unwrap();
+        satrec.name = Some("ISS (ZARYA)".to_string());
+
+        let text = write_3le(&satrec).unwrap();
+        let parsed = crate::io::parse(&text).unwrap();
+        assert_eq!(parsed.name, Some("ISS (ZARYA)".to_string()));
+        assert_eq!(parsed.no_kozai, satrec.no_kozai);
+    }
+
+    #[test]
+    fn test_round_trip_catalog() {
+        for (tle1, tle2) in verification_tles() {
+            let original = crate::io::parse_satrec(tle1, tle2).unwrap();
+            let (line1, line2) = write_tle(&original).unwrap();
+            let parsed = crate::io::parse_satrec(&line1, &line2).unwrap();
+
+            assert_eq!(parsed.satnum, original.satnum, "{}", tle1);
+            assert_eq!(parsed.classification, original.classification, "{}", tle1);
+            assert_eq!(parsed.intldesg, original.intldesg, "{}", tle1);
+            assert_eq!(parsed.ephtype, original.ephtype, "{}", tle1);
+            assert_eq!(parsed.elnum, original.elnum, "{}", tle1);
+            assert_eq!(parsed.revnum, original.revnum, "{}", tle2);
+            assert_eq!(parsed.epochyr, original.epochyr, "{}", tle1);
+            assert_eq!(parsed.epochdays, original.epochdays, "{}", tle1);
+            assert_eq!(parsed.ndot, original.ndot, "{}", tle1);
+            assert_eq!(parsed.nddot, original.nddot, "{}", tle1);
+            assert_eq!(parsed.bstar, original.bstar, "{}", tle1);
+            assert_eq!(parsed.inclo, original.inclo, "{}", tle2);
+            assert_eq!(parsed.nodeo, original.nodeo, "{}", tle2);
+            assert_eq!(parsed.ecco, original.ecco, "{}", tle2);
+            assert_eq!(parsed.argpo, original.argpo, "{}", tle2);
+            assert_eq!(parsed.mo, original.mo, "{}", tle2);
+            assert_eq!(parsed.no_kozai, original.no_kozai, "{}", tle2);
+            assert_eq!(parsed.jdsatepoch, original.jdsatepoch, "{}", tle1);
+
+            assert_eq!(&line1[..43], &tle1[..43]);
+            assert_eq!(&line2[..68], &tle2[..68]);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_initialized() {
+        // sgp4init replaces `no` with the un-Kozai'd mean motion; the writer must not.
+        for (tle1, tle2) in verification_tles() {
+            let original = match crate::io::twoline2satrec(tle1, tle2) {
+                Ok(rec) => rec,
+                Err(_) => continue,
+            };
+
+            let (line1, line2) = write_tle(&original).unwrap();
+            let parsed = crate::io::twoline2satrec(&line1, &line2).unwrap();
+
+            assert_eq!(parsed.no_kozai, original.no_kozai, "{}", tle2);
+            assert_eq!(parsed.no, original.no, "{}", tle2);
+            assert_eq!(parsed.ecco, original.ecco, "{}", tle2);
+            assert_eq!(parsed.inclo, original.inclo, "{}", tle2);
+        }
+    }
+}
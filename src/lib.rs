@@ -1,13 +1,23 @@
 extern crate chrono;
 
+pub mod anomaly;
 pub mod constants;
 pub mod doppler_factor;
+pub mod elements;
+pub mod eop;
 pub mod ext;
+pub mod frames;
+pub mod horizon;
 pub mod io;
+pub mod passes;
 pub mod propogation;
+pub mod refraction;
+pub mod time;
 pub mod transforms;
 
-#[derive(Debug, PartialEq)]
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
 /// Standard three-component vector (x,y,z)
 pub struct Vec3 {
     pub x: f64,
@@ -56,25 +66,196 @@ pub struct Bearing {
     pub range: f64,
 }
 
+#[derive(Debug, PartialEq)]
+/// Rates of change of a `Bearing`
+pub struct BearingRate {
+    /// Azimuth rate in radians per second
+    pub azimuth: f64,
+
+    /// Elevation rate in radians per second
+    pub elevation: f64,
+
+    /// Range rate in km/s, positive when receding
+    pub range: f64,
+}
+
 impl Vec3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+
     pub fn range(&self, to: &Vec3) -> f64 {
-        (((self.x - to.x).powf(2.0)) + ((self.y - to.y).powf(2.0)) + ((self.z - to.z).powf(2.0)))
-            .sqrt()
+        (*self - *to).norm()
     }
 
     pub fn add(&self, by: &Vec3) -> Vec3 {
+        *self + *by
+    }
+
+    pub fn subtract(&self, by: &Vec3) -> Vec3 {
+        *self - *by
+    }
+
+    pub fn dot(&self, other: &Vec3) -> f64 {
+        (self.x * other.x) + (self.y * other.y) + (self.z * other.z)
+    }
+
+    pub fn cross(&self, other: &Vec3) -> Vec3 {
         Vec3 {
-            x: self.x + by.x,
-            y: self.y + by.y,
-            z: self.z + by.z,
+            x: (self.y * other.z) - (self.z * other.y),
+            y: (self.z * other.x) - (self.x * other.z),
+            z: (self.x * other.y) - (self.y * other.x),
         }
     }
 
-    pub fn subtract(&self, by: &Vec3) -> Vec3 {
+    /// Length of the vector.
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Vector of length one in the same direction. The zero vector gives NaNs.
+    pub fn unit(&self) -> Vec3 {
+        *self / self.norm()
+    }
+
+    /// Angle between two vectors in radians, in [0, π].
+    pub fn angle(&self, other: &Vec3) -> f64 {
+        // atan2 keeps precision for nearly parallel vectors, where acos does not.
+        self.cross(other).norm().atan2(self.dot(other))
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
         Vec3 {
-            x: self.x - by.x,
-            y: self.y - by.y,
-            z: self.z - by.z,
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, scale: f64) -> Vec3 {
+        Vec3 {
+            x: self.x * scale,
+            y: self.y * scale,
+            z: self.z * scale,
+        }
+    }
+}
+
+impl Mul<Vec3> for f64 {
+    type Output = Vec3;
+
+    fn mul(self, vector: Vec3) -> Vec3 {
+        vector * self
+    }
+}
+
+impl Div<f64> for Vec3 {
+    type Output = Vec3;
+
+    fn div(self, scale: f64) -> Vec3 {
+        Vec3 {
+            x: self.x / scale,
+            y: self.y / scale,
+            z: self.z / scale,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+/// 3x3 matrix by rows, applied to column vectors as `m * v`.
+pub struct Mat3(pub [[f64; 3]; 3]);
+
+impl Mat3 {
+    pub fn identity() -> Mat3 {
+        Mat3([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Rotation of the coordinate axes by `angle` about x (Vallado's ROT1).
+    pub fn rotation_x(angle: f64) -> Mat3 {
+        let (s, c) = angle.sin_cos();
+        Mat3([[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]])
+    }
+
+    /// Rotation of the coordinate axes by `angle` about y (Vallado's ROT2).
+    pub fn rotation_y(angle: f64) -> Mat3 {
+        let (s, c) = angle.sin_cos();
+        Mat3([[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]])
+    }
+
+    /// Rotation of the coordinate axes by `angle` about z (Vallado's ROT3).
+    pub fn rotation_z(angle: f64) -> Mat3 {
+        let (s, c) = angle.sin_cos();
+        Mat3([[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// The transpose, which is the inverse of a rotation.
+    pub fn transpose(&self) -> Mat3 {
+        let m = &self.0;
+        Mat3([
+            [m[0][0], m[1][0], m[2][0]],
+            [m[0][1], m[1][1], m[2][1]],
+            [m[0][2], m[1][2], m[2][2]],
+        ])
+    }
+}
+
+/// Composition: `a * b` applies `b` first.
+impl Mul for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, other: Mat3) -> Mat3 {
+        let (a, b) = (&self.0, &other.0);
+        let mut m = [[0.0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (a[i][0] * b[0][j]) + (a[i][1] * b[1][j]) + (a[i][2] * b[2][j]);
+            }
+        }
+        Mat3(m)
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        let m = &self.0;
+        Vec3 {
+            x: (m[0][0] * v.x) + (m[0][1] * v.y) + (m[0][2] * v.z),
+            y: (m[1][0] * v.x) + (m[1][1] * v.y) + (m[1][2] * v.z),
+            z: (m[2][0] * v.x) + (m[2][1] * v.y) + (m[2][2] * v.z),
         }
     }
 }
@@ -94,6 +275,25 @@ mod tests {
         }
     }
 
+    /// Line pairs of Vallado's verification catalog, `testdata/SGP4-VER.TLE`. Line 2 keeps
+    /// the start, stop and step minutes that follow the elements.
+    pub fn verification_tles() -> Vec<(&'static str, &'static str)> {
+        let lines = include_str!("../testdata/SGP4-VER.TLE")
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .collect::<Vec<&str>>();
+
+        lines.chunks(2).map(|pair| (pair[0], pair[1])).collect()
+    }
+
+    /// The verification catalog as satrecs, leaving out 33334, which fails to initialize.
+    pub fn verification_satrecs() -> Vec<crate::io::Satrec> {
+        verification_tles()
+            .into_iter()
+            .filter_map(|(tle1, tle2)| crate::io::twoline2satrec(tle1, &tle2[..69]).ok())
+            .collect()
+    }
+
     use crate::Vec3;
     struct TrackEntry {
         time: f64,
@@ -101,72 +301,66 @@ mod tests {
         known_vel: Vec3,
     }
 
+    /// Initialize with the WGS-72 constants used for Vallado's verification output.
+    fn wgs72_satrec(tle1: &str, tle2: &str) -> crate::io::Satrec {
+        let options = crate::io::InitOptions {
+            gravity_model: crate::constants::GravityModel::Wgs72,
+            ..crate::io::InitOptions::default()
+        };
+
+        crate::io::twoline2satrec_with_options(tle1, tle2, &options).unwrap()
+    }
+
+    fn assert_track(satrec: &mut crate::io::Satrec, known_track: Vec<TrackEntry>) {
+        for entry in known_track {
+            let result = match crate::propogation::sgp4::sgp4(satrec, entry.time) {
+                Ok(res) => res,
+                Err(err) => panic!("Propogation Error: {:#?} (code {})", err, err.code())
+            };
+
+            // within a millimeter and a micrometer per second.
+            assert!(result.position.range(&entry.known_pos) < 1e-6);
+            assert!(result.velocity.range(&entry.known_vel) < 1e-9);
+        }
+    }
+
     #[test]
     fn leo_tle() {
         let tle1 = "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0    8";
         let tle2 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518  105";
 
-        let mut satrec = crate::io::twoline2satrec(tle1, tle2).unwrap();
+        let mut satrec = wgs72_satrec(tle1, tle2);
 
         let known_track: Vec<TrackEntry> = vec![
             TrackEntry {
                 time: 0.0,
                 known_pos: Vec3 {
-                    x: 2328.97048951,
-                    y: -5995.22076416,
-                    z: 1719.97067261,
+                    x: 2328.96975262,
+                    y: -5995.22051338,
+                    z: 1719.97297192,
                 },
                 known_vel: Vec3 {
-                    x: 2.91207230,
-                    y: -0.98341546,
-                    z: -7.09081703,
+                    x: 2.912073281,
+                    y: -0.983417956,
+                    z: -7.090816210,
                 },
             },
             TrackEntry {
                 time: 360.0,
                 known_pos: Vec3 {
-                    x: 2456.10705566,
-                    y: -6071.93853760,
-                    z: 1222.89727783,
+                    x: 2456.10706533,
+                    y: -6071.93855503,
+                    z: 1222.89768554,
                 },
                 known_vel: Vec3 {
-                    x: 2.67938992,
-                    y: -0.44829041,
-                    z: -7.22879231,
+                    x: 2.679390040,
+                    y: -0.448290811,
+                    z: -7.228792155,
                 },
             },
         ];
 
-        for entry in known_track {
-            let result = match crate::propogation::sgp4::sgp4(&mut satrec, entry.time) {
-                Ok(res) => res,
-                Err(err) => panic!("Propogation Error: {:#?} (code {})", err, err.code())
-            };
-
-            // println!("pos @{} {:#?}", entry.time, result.position);
-            // println!("vel @{} {:#?}", entry.time, result.velocity);
-            let diff_pos = result.position.subtract(&entry.known_pos);
-            // println!("distance to pos: @{} {:#?}", entry.time, diff_pos);
-
-            let diff_vel = result.velocity.subtract(&entry.known_vel);
-            // println!("distance to vel: @{} {:#?}", entry.time, diff_vel);
-            // println!(
-            //     "{} < {} = {}",
-            //     diff_pos.x.abs(),
-            //     0.1,
-            //     diff_pos.x.abs() < 0.1
-            // );
-            assert!(diff_pos.x.abs() < 0.1);
-            assert!(diff_pos.y.abs() < 0.1);
-            assert!(diff_pos.z.abs() < 0.1);
-
-            assert!(diff_vel.x.abs() < 0.1);
-            assert!(diff_vel.y.abs() < 0.1);
-            assert!(diff_vel.z.abs() < 0.1);
-
-            // assert_eq!(result.position.range(&entry.known_pos), 0.0);
-            // assert_eq!(result.velocity.range(&entry.known_vel), 0.0);
-        }
+        assert_track(&mut satrec, known_track);
     }
 
     #[test]
@@ -174,65 +368,173 @@ mod tests {
         let tle1 = "1 11801U          80230.29629788  .01431103  00000-0  14311-1        ";
         let tle2 = "2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848      ";
 
-        let mut satrec = crate::io::twoline2satrec(tle1, tle2).unwrap();
+        let mut satrec = wgs72_satrec(tle1, tle2);
 
         let known_track: Vec<TrackEntry> = vec![
             TrackEntry {
                 time: 0.0,
                 known_pos: Vec3 {
-                    x: 7473.37066650,
-                    y: 428.95261765,
-                    z: 5828.74786377,
+                    x: 7473.37102491,
+                    y: 428.94748312,
+                    z: 5828.74846783,
                 },
                 known_vel: Vec3 {
-                    x: 5.10715413,
-                    y: 6.44468284,
-                    z: -0.18613096,
+                    x: 5.107155391,
+                    y: 6.444680305,
+                    z: -0.186133297,
                 },
             },
             TrackEntry {
                 time: 360.0,
                 known_pos: Vec3 {
-                    x: -3305.22537232,
-                    y: 32410.86328125,
-                    z: -24697.17675781,
+                    x: -3305.22148694,
+                    y: 32410.84323331,
+                    z: -24697.16974954,
                 },
                 known_vel: Vec3 {
-                    x: -1.30113538,
-                    y: -1.15131518,
-                    z: -0.28333528,
+                    x: -1.301137319,
+                    y: -1.151315600,
+                    z: -0.283335823,
                 },
             },
         ];
 
-        for entry in known_track {
-            let result = match crate::propogation::sgp4::sgp4(&mut satrec, entry.time) {
-                Ok(res) => res,
-                Err(err) => panic!("Propogation Error: {:#?} (code {})", err, err.code())
-            };
+        assert_track(&mut satrec, known_track);
+    }
+
+    /// An entry of `testdata/tcppver.out`: a state vector or a propagation error code.
+    #[derive(Debug, PartialEq)]
+    enum VerificationEntry {
+        State(f64, Vec3, Vec3),
+        Error(f64, u8),
+    }
+
+    fn parse_verification_output(text: &str) -> Vec<(String, Vec<VerificationEntry>)> {
+        let mut objects: Vec<(String, Vec<VerificationEntry>)> = vec![];
+
+        for line in text.lines() {
+            if line.ends_with(" xx") {
+                objects.push((line.trim_end_matches(" xx").to_string(), vec![]));
+            } else if line.starts_with("# *** error") {
+                // # *** error: t:=   494.202867 *** code =   1
+                let fields = line.split_whitespace().collect::<Vec<&str>>();
+                let entry = VerificationEntry::Error(
+                    fields[4].parse().unwrap(),
+                    fields[8].parse().unwrap(),
+                );
+                objects.last_mut().unwrap().1.push(entry);
+            } else if !line.starts_with('#') {
+                let v = line
+                    .split_whitespace()
+                    .map(|field| field.parse::<f64>().unwrap())
+                    .collect::<Vec<f64>>();
+                let entry = VerificationEntry::State(
+                    v[0],
+                    Vec3 { x: v[1], y: v[2], z: v[3] },
+                    Vec3 { x: v[4], y: v[5], z: v[6] },
+                );
+                objects.last_mut().unwrap().1.push(entry);
+            }
+        }
+
+        objects
+    }
 
-            // println!("pos @{} {:#?}", entry.time, result.position);
-            // println!("vel @{} {:#?}", entry.time, result.velocity);
-            let diff_pos = result.position.subtract(&entry.known_pos);
-            // println!("distance to pos: @{} {:#?}", entry.time, diff_pos);
+    /// Propagate an object of the verification catalog the way Vallado's test driver does:
+    /// the epoch first, then from the start time in steps, finishing exactly on the stop time.
+    fn run_verification(tle1: &str, tle2: &str) -> Vec<VerificationEntry> {
+        let mut entries = vec![];
+
+        let options = crate::io::InitOptions {
+            gravity_model: crate::constants::GravityModel::Wgs72,
+            ..crate::io::InitOptions::default()
+        };
+
+        let mut satrec = match crate::io::twoline2satrec_with_options(tle1, &tle2[..69], &options) {
+            Ok(satrec) => satrec,
+            Err(crate::io::SatrecParseError::Sgp4InitError(err)) => {
+                return vec![VerificationEntry::Error(0.0, err.code())];
+            }
+            Err(err) => panic!("{:?}", err),
+        };
+
+        let range = tle2[69..]
+            .split_whitespace()
+            .map(|field| field.parse::<f64>().unwrap())
+            .collect::<Vec<f64>>();
+        let (start, stop, step) = (range[0], range[1], range[2]);
+
+        let mut times = vec![0.0];
+        let mut tsince = start;
+        if tsince.abs() > 1e-8 {
+            tsince -= step;
+        }
+        while tsince < stop {
+            tsince += step;
+            if tsince > stop {
+                tsince = stop;
+            }
+            times.push(tsince);
+        }
 
-            let diff_vel = result.velocity.subtract(&entry.known_vel);
-            // println!("distance to vel: @{} {:#?}", entry.time, diff_vel);
-            // println!(
-            //     "{} < {} = {}",
-            //     diff_pos.x.abs(),
-            //     0.1,
-            //     diff_pos.x.abs() < 0.1
-            // );
+        for t in times {
+            match crate::propogation::sgp4::sgp4(&mut satrec, t) {
+                Ok(res) => entries.push(VerificationEntry::State(t, res.position, res.velocity)),
+                Err(err) => {
+                    entries.push(VerificationEntry::Error(t, err.code()));
+                    break;
+                }
+            }
+        }
 
-            // TODO: these seem off.
-            assert!(diff_pos.x.abs() < 50.0);
-            assert!(diff_pos.y.abs() < 50.0);
-            assert!(diff_pos.z.abs() < 50.0);
+        entries
+    }
 
-            assert!(diff_vel.x.abs() < 0.1);
-            assert!(diff_vel.y.abs() < 0.1);
-            assert!(diff_vel.z.abs() < 0.1);
+    #[test]
+    fn verification_catalog() {
+        let expected = parse_verification_output(include_str!("../testdata/tcppver.out"));
+
+        let tles = verification_tles();
+        assert_eq!(tles.len(), expected.len());
+
+        for ((tle1, tle2), (satnum, expected_entries)) in tles.into_iter().zip(expected.iter()) {
+            assert_eq!(tle1[2..7].trim_start_matches('0'), satnum);
+
+            let entries = run_verification(tle1, tle2);
+            assert_eq!(entries.len(), expected_entries.len(), "{}", satnum);
+
+            for (entry, expected_entry) in entries.iter().zip(expected_entries) {
+                match (entry, expected_entry) {
+                    (
+                        VerificationEntry::State(t, position, velocity),
+                        VerificationEntry::State(expected_t, expected_position, expected_velocity),
+                    ) => {
+                        assert_diff(*t, *expected_t, 1e-8);
+                        assert!(
+                            position.range(expected_position) < 1e-6,
+                            "{} @ {}: position off by {} km",
+                            satnum,
+                            t,
+                            position.range(expected_position)
+                        );
+                        assert!(
+                            velocity.range(expected_velocity) < 1e-9,
+                            "{} @ {}: velocity off by {} km/s",
+                            satnum,
+                            t,
+                            velocity.range(expected_velocity)
+                        );
+                    }
+                    (
+                        VerificationEntry::Error(t, code),
+                        VerificationEntry::Error(expected_t, expected_code),
+                    ) => {
+                        assert_diff(*t, *expected_t, 1e-6);
+                        assert_eq!(code, expected_code, "{} @ {}", satnum, t);
+                    }
+                    _ => panic!("{}: got {:?}, expected {:?}", satnum, entry, expected_entry),
+                }
+            }
         }
     }
 
@@ -246,5 +548,55 @@ mod tests {
             SatrecParseError::SatrecMultiError(0, Box::new(SatrecParseError::InvalidTLEBadLineCount))
         ]);
     }
-}
 
+    #[test]
+    fn test_vec3() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(-2.0, 0.5, 4.0);
+
+        assert_eq!(a + b, Vec3::new(-1.0, 2.5, 7.0));
+        assert_eq!(a - b, Vec3::new(3.0, 1.5, -1.0));
+        assert_eq!(-a, Vec3::new(-1.0, -2.0, -3.0));
+        assert_eq!(a * 2.0, 2.0 * a);
+        assert_eq!(a * 2.0 / 2.0, a);
+        assert_eq!(a.add(&b), a + b);
+        assert_eq!(a.subtract(&b), a - b);
+
+        assert_eq!(a.dot(&b), 11.0);
+        assert_eq!(a.cross(&b), Vec3::new(6.5, -10.0, 4.5));
+        assert_eq!(a.cross(&b).dot(&a), 0.0);
+        assert_eq!(Vec3::new(3.0, 4.0, 12.0).norm(), 13.0);
+        assert_eq!(a.range(&b), (a - b).norm());
+        assert_similar(a.unit().norm(), 1.0);
+
+        let x = Vec3::new(2.0, 0.0, 0.0);
+        assert_similar(x.angle(&Vec3::new(0.0, 3.0, 0.0)), std::f64::consts::FRAC_PI_2);
+        assert_similar(x.angle(&-x), std::f64::consts::PI);
+        assert_eq!(x.angle(&x), 0.0);
+        assert_similar(x.angle(&Vec3::new(1.0, 1e-9, 0.0)), 1e-9);
+    }
+
+    #[test]
+    fn test_mat3() {
+        use crate::Mat3;
+
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        let angle = 0.3;
+
+        // rotating the axes by +angle turns vectors by -angle.
+        let rotated = Mat3::rotation_z(angle) * Vec3::new(1.0, 0.0, 0.0);
+        assert_similar(rotated.x, angle.cos());
+        assert_similar(rotated.y, -angle.sin());
+
+        for m in [Mat3::rotation_x(angle), Mat3::rotation_y(angle), Mat3::rotation_z(angle)] {
+            let back = m.transpose() * (m * v);
+            assert!(back.range(&v) < 1e-15);
+            assert_similar((m * v).norm(), v.norm());
+        }
+
+        let composed = Mat3::rotation_x(0.1) * Mat3::rotation_z(0.2);
+        assert!((composed * v).range(&(Mat3::rotation_x(0.1) * (Mat3::rotation_z(0.2) * v))) < 1e-15);
+        assert!((Mat3::rotation_z(0.2) * Mat3::rotation_z(-0.2) * v).range(&(Mat3::identity() * v)) < 1e-15);
+        assert_eq!(Mat3::identity().transpose(), Mat3::identity());
+    }
+}
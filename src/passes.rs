@@ -0,0 +1,437 @@
+//! Pass prediction: when a satellite rises above, culminates and sets below an
+//! observer's elevation mask.
+//!
+//! The mask is a minimum elevation in radians, or a
+//! [`HorizonMask`](crate::horizon::HorizonMask) that follows the terrain around the
+//! observer.
+
+use crate::constants::*;
+use crate::horizon::ElevationMask;
+use crate::io::Satrec;
+use crate::propogation::gstime::gstime;
+use crate::propogation::minutes_since_epoch;
+use crate::propogation::sgp4::{sgp4_with_cache, SGP4Error};
+use crate::time::Epoch;
+use crate::transforms::{ecf_to_look_angles, eci_to_ecf};
+use crate::{Bearing, Geodedic};
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Times are refined to this many minutes (0.6 ms).
+const TOLERANCE: f64 = 1e-5;
+
+/// Half-width, in minutes, of the central difference used for rates.
+const RATE_STEP: f64 = 1e-4;
+
+/// Samples per orbit at perigee speed when scanning for elevation extrema.
+const SAMPLES_PER_ORBIT: f64 = 60.0;
+
+/// The look angles to a satellite at one instant of a pass.
+#[derive(Debug, PartialEq)]
+pub struct PassEvent {
+    pub time: DateTime<Utc>,
+
+    /// Time of the event, in minutes since the satrec epoch.
+    pub tsince: f64,
+
+    pub look_angles: Bearing,
+}
+
+/// A pass of a satellite above an observer's elevation mask.
+///
+/// With a horizon mask, a pass is an interval of visibility: a satellite passing behind
+/// an obstruction sets, and rises again as a separate pass. Passes already in progress
+/// at the start of the search window rise at the start of the window, and those still in
+/// progress at its end set at the end.
+#[derive(Debug, PartialEq)]
+pub struct Pass {
+    /// Acquisition of signal, when the satellite rises through the mask.
+    pub rise: PassEvent,
+
+    /// Maximum elevation.
+    pub culmination: PassEvent,
+
+    /// Loss of signal, when the satellite drops through the mask.
+    pub set: PassEvent,
+}
+
+struct Tracker<'a, M> {
+    satrec: &'a Satrec,
+    observer: &'a Geodedic,
+    mask: M,
+}
+
+impl<'a, M: ElevationMask> Tracker<'a, M> {
+    fn look_angles(&self, tsince: f64) -> Result<Bearing, SGP4Error> {
+        let state = sgp4_with_cache(self.satrec, tsince, None)?;
+        let gmst = gstime(self.satrec.jdsatepoch + tsince / MINUTES_PER_DAY);
+
+        Ok(ecf_to_look_angles(
+            self.observer,
+            &eci_to_ecf(&state.position, gmst),
+        ))
+    }
+
+    /// Elevation, and elevation above the mask.
+    fn elevation(&self, tsince: f64) -> Result<(f64, f64), SGP4Error> {
+        let look_angles = self.look_angles(tsince)?;
+        let clearance = look_angles.elevation - self.mask.min_elevation(look_angles.azimuth);
+        Ok((look_angles.elevation, clearance))
+    }
+
+    /// Rates of the elevation, and of the elevation above the mask.
+    fn rates(&self, tsince: f64) -> Result<(f64, f64), SGP4Error> {
+        let before = self.elevation(tsince - RATE_STEP)?;
+        let after = self.elevation(tsince + RATE_STEP)?;
+        Ok((
+            (after.0 - before.0) / (2.0 * RATE_STEP),
+            (after.1 - before.1) / (2.0 * RATE_STEP),
+        ))
+    }
+
+    fn event(&self, tsince: f64) -> Result<PassEvent, SGP4Error> {
+        Ok(PassEvent {
+            time: self.satrec.epoch() + Duration::nanoseconds((tsince * 60e9).round() as i64),
+            tsince,
+            look_angles: self.look_angles(tsince)?,
+        })
+    }
+}
+
+/// Bisects `f` over `[a, b]`, where it changes sign, to [`TOLERANCE`].
+fn bisect<F>(f: F, mut a: f64, mut b: f64) -> Result<f64, SGP4Error>
+where
+    F: Fn(f64) -> Result<f64, SGP4Error>,
+{
+    let positive_at_a = f(a)? > 0.0;
+
+    while b - a > TOLERANCE {
+        let mid = 0.5 * (a + b);
+        if (f(mid)? > 0.0) == positive_at_a {
+            a = mid;
+        } else {
+            b = mid;
+        }
+    }
+
+    Ok(0.5 * (a + b))
+}
+
+/// Finds the passes of `satrec` over `observer` between `start` and `stop`, above an
+/// elevation mask: a minimum elevation in radians or a
+/// [`HorizonMask`](crate::horizon::HorizonMask).
+///
+/// The window is split at the maxima and minima of the elevation above the mask, found
+/// as roots of its rate, and where the azimuth crosses a corner or step of the mask, so
+/// the elevation above the mask is monotonic on each piece and crosses zero at most once.
+/// Rise and set are then roots of the elevation above the mask. Brief and grazing passes
+/// that peak between samples are found as well, as long as a maximum and the following
+/// minimum do not fall within one sampling step (1/60 of the orbit at perigee speed).
+///
+/// Look angles use GMST with UT1 = UTC, as in [`eci_to_ecf`].
+pub fn find_passes<T: Into<Epoch>, M: ElevationMask>(
+    satrec: &Satrec,
+    observer: &Geodedic,
+    start: T,
+    stop: T,
+    mask: M,
+) -> Result<Vec<Pass>, SGP4Error> {
+    let tracker = Tracker {
+        satrec,
+        observer,
+        mask,
+    };
+    let t0 = minutes_since_epoch(satrec, start.into());
+    let t1 = minutes_since_epoch(satrec, stop.into());
+    if t1 <= t0 {
+        return Ok(Vec::new());
+    }
+
+    let ecc = satrec.ecco;
+    let perigee_rate = (1.0 + ecc).sqrt() / (1.0 - ecc).powf(1.5);
+    let step = TWO_PI / satrec.no / perigee_rate / SAMPLES_PER_ORBIT;
+
+    // the window's ends, the extrema within it of the elevation, for culminations, and
+    // of the elevation above the mask, for rise and set, and the crossings of the mask's
+    // breaks, where the elevation above the mask can jump.
+    let breaks = tracker.mask.breaks();
+    let azimuth_offset = |t: f64, azimuth: f64| -> Result<f64, SGP4Error> {
+        Ok((tracker.look_angles(t)?.azimuth - azimuth).sin())
+    };
+
+    let mut boundaries = vec![t0];
+    let mut rates = tracker.rates(t0)?;
+    let mut azimuth = tracker.look_angles(t0)?.azimuth;
+    let mut t = t0;
+    while t < t1 {
+        let next = (t + step).min(t1);
+        let next_rates = tracker.rates(next)?;
+        let next_azimuth = tracker.look_angles(next)?.azimuth;
+
+        if (rates.0 > 0.0) != (next_rates.0 > 0.0) {
+            boundaries.push(bisect(|t| Ok(tracker.rates(t)?.0), t, next)?);
+        }
+        if (rates.1 > 0.0) != (next_rates.1 > 0.0) {
+            boundaries.push(bisect(|t| Ok(tracker.rates(t)?.1), t, next)?);
+        }
+        for &at in &breaks {
+            if ((azimuth - at).sin() > 0.0) != ((next_azimuth - at).sin() > 0.0) {
+                boundaries.push(bisect(|t| azimuth_offset(t, at), t, next)?);
+            }
+        }
+
+        t = next;
+        rates = next_rates;
+        azimuth = next_azimuth;
+    }
+    boundaries.push(t1);
+    boundaries.sort_by(|a, b| a.total_cmp(b));
+
+    let mut passes = Vec::new();
+    let mut rise = None;
+    let (elevation, mut clearance) = tracker.elevation(t0)?;
+    let mut culmination = (t0, elevation);
+    if clearance >= 0.0 {
+        rise = Some(t0);
+    }
+
+    for pair in boundaries.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let visible_at_a = clearance >= 0.0;
+        let (elevation, next_clearance) = tracker.elevation(b)?;
+        let visible_at_b = next_clearance >= 0.0;
+
+        if visible_at_a != visible_at_b {
+            let crossing = bisect(|t| Ok(tracker.elevation(t)?.1), a, b)?;
+
+            // behind a horizon mask, the elevation can peak where it crosses the mask.
+            let at_crossing = (crossing, tracker.elevation(crossing)?.0);
+
+            if visible_at_b {
+                rise = Some(crossing);
+                culmination = if elevation > at_crossing.1 {
+                    (b, elevation)
+                } else {
+                    at_crossing
+                };
+            } else if let Some(rise) = rise.take() {
+                if at_crossing.1 > culmination.1 {
+                    culmination = at_crossing;
+                }
+
+                passes.push(Pass {
+                    rise: tracker.event(rise)?,
+                    culmination: tracker.event(culmination.0)?,
+                    set: tracker.event(crossing)?,
+                });
+            }
+        } else if visible_at_b && elevation > culmination.1 {
+            culmination = (b, elevation);
+        }
+
+        clearance = next_clearance;
+    }
+
+    if let Some(rise) = rise {
+        passes.push(Pass {
+            rise: tracker.event(rise)?,
+            culmination: tracker.event(culmination.0)?,
+            set: tracker.event(t1)?,
+        });
+    }
+
+    Ok(passes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::twoline2satrec;
+    use chrono::TimeZone;
+
+    fn iss() -> Satrec {
+        twoline2satrec(
+            "1 25544U 98067A   19070.20068744  .00000619  00000-0  17310-4 0  9990",
+            "2 25544  51.6414 128.3903 0004102  93.2843   5.7821 15.52799004160030",
+        )
+        .unwrap()
+    }
+
+    fn observer() -> Geodedic {
+        Geodedic {
+            longitude: -122.0308 * DEG_2_RAD,
+            latitude: 36.9613422 * DEG_2_RAD,
+            height: 0.370,
+        }
+    }
+
+    #[test]
+    fn test_passes_match_dense_sampling() {
+        let satrec = iss();
+        let observer = observer();
+        let tracker = Tracker {
+            satrec: &satrec,
+            observer: &observer,
+            mask: 0.0,
+        };
+
+        let start = Utc.with_ymd_and_hms(2019, 3, 11, 0, 0, 0).unwrap();
+        let stop = start + Duration::days(2);
+        let mask = 10.0 * DEG_2_RAD;
+        let passes = find_passes(&satrec, &observer, start, stop, mask).unwrap();
+        assert!(!passes.is_empty());
+
+        // every 5 seconds
+        let t0 = minutes_since_epoch(&satrec, start.into());
+        let mut sampled = Vec::new();
+        let mut above = false;
+        for i in 0..(2 * 24 * 720) {
+            let t = t0 + f64::from(i) / 12.0;
+            let now_above = tracker.elevation(t).unwrap().0 >= mask;
+            if now_above && !above {
+                sampled.push(t);
+            }
+            above = now_above;
+        }
+        assert_eq!(passes.len(), sampled.len());
+
+        for (pass, sampled_rise) in passes.iter().zip(sampled) {
+            assert!((pass.rise.tsince - sampled_rise).abs() < 1.0 / 12.0);
+            assert!(pass.rise.tsince < pass.culmination.tsince);
+            assert!(pass.culmination.tsince < pass.set.tsince);
+
+            crate::tests::assert_diff(pass.rise.look_angles.elevation, mask, 1e-6);
+            crate::tests::assert_diff(pass.set.look_angles.elevation, mask, 1e-6);
+
+            // the culmination is a maximum
+            let peak = pass.culmination.look_angles.elevation;
+            for &offset in &[-0.01, 0.01] {
+                assert!(
+                    tracker
+                        .elevation(pass.culmination.tsince + offset)
+                        .unwrap()
+                        .0
+                        < peak
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_grazing_pass() {
+        let satrec = iss();
+        let observer = observer();
+
+        let start = Utc.with_ymd_and_hms(2019, 3, 11, 0, 0, 0).unwrap();
+        let stop = start + Duration::days(1);
+        let passes = find_passes(&satrec, &observer, start, stop, 0.0).unwrap();
+
+        // a mask just below the highest culmination leaves a pass only a few seconds long
+        let highest = passes
+            .iter()
+            .max_by(|a, b| {
+                let a = a.culmination.look_angles.elevation;
+                a.partial_cmp(&b.culmination.look_angles.elevation).unwrap()
+            })
+            .unwrap();
+        let peak = highest.culmination.look_angles.elevation;
+        let grazing = find_passes(&satrec, &observer, start, stop, peak - 1e-5).unwrap();
+        assert_eq!(grazing.len(), 1);
+
+        let pass = &grazing[0];
+        let duration = pass.set.tsince - pass.rise.tsince;
+        assert!(duration > 0.0 && duration < 0.5, "{}", duration);
+        assert!((pass.culmination.tsince - highest.culmination.tsince).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_window_clipping() {
+        let satrec = iss();
+        let observer = observer();
+
+        let start = Utc.with_ymd_and_hms(2019, 3, 11, 0, 0, 0).unwrap();
+        let stop = start + Duration::days(1);
+        let passes = find_passes(&satrec, &observer, start, stop, 0.0).unwrap();
+        let pass = &passes[0];
+
+        // a window starting and stopping during the pass
+        let inner = find_passes(
+            &satrec,
+            &observer,
+            pass.rise.time + Duration::seconds(60),
+            pass.set.time - Duration::seconds(60),
+            0.0,
+        )
+        .unwrap();
+
+        assert_eq!(inner.len(), 1);
+        let ms = |a: DateTime<Utc>, b: DateTime<Utc>| (a - b).num_milliseconds().abs();
+        assert!(ms(inner[0].rise.time, pass.rise.time + Duration::seconds(60)) <= 1);
+        assert!(ms(inner[0].set.time, pass.set.time - Duration::seconds(60)) <= 1);
+        assert!((inner[0].culmination.tsince - pass.culmination.tsince).abs() < 1e-3);
+
+        assert_eq!(
+            find_passes(&satrec, &observer, stop, start, 0.0).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_horizon_mask() {
+        use crate::horizon::HorizonMask;
+
+        let satrec = iss();
+        let observer = observer();
+
+        // a ridge to the south and a building to the north-east
+        let mask =
+            HorizonMask::parse("0, 2\n40, 2\n40, 35\n80, 35\n80, 2\n150, 8\n180, 25\n210, 8\n")
+                .unwrap();
+        let tracker = Tracker {
+            satrec: &satrec,
+            observer: &observer,
+            mask: &mask,
+        };
+
+        let start = Utc.with_ymd_and_hms(2019, 3, 11, 0, 0, 0).unwrap();
+        let stop = start + Duration::days(2);
+        let passes = find_passes(&satrec, &observer, start, stop, &mask).unwrap();
+
+        let t0 = minutes_since_epoch(&satrec, start.into());
+        let mut sampled = 0;
+        let mut visible = false;
+        for i in 0..(2 * 24 * 720) {
+            let t = t0 + f64::from(i) / 12.0;
+            let now_visible = tracker.elevation(t).unwrap().1 >= 0.0;
+            if now_visible && !visible {
+                sampled += 1;
+            }
+            visible = now_visible;
+        }
+        assert_eq!(passes.len(), sampled);
+
+        for pass in &passes {
+            // the elevation above the mask changes sign at rise and set, which may be at
+            // a step of the mask rather than at its elevation.
+            let clearance = |t: f64| tracker.elevation(t).unwrap().1;
+            let margin = 2.0 * TOLERANCE;
+            assert!(clearance(pass.rise.tsince - margin) < 0.0);
+            assert!(clearance(pass.rise.tsince + margin) >= 0.0);
+            assert!(clearance(pass.set.tsince - margin) >= 0.0);
+            assert!(clearance(pass.set.tsince + margin) < 0.0);
+
+            assert!(pass.rise.tsince <= pass.culmination.tsince);
+            assert!(pass.culmination.tsince <= pass.set.tsince);
+        }
+
+        // the mask only removes visibility
+        let open = find_passes(&satrec, &observer, start, stop, 2.0 * DEG_2_RAD).unwrap();
+        let duration = |passes: &[Pass]| -> f64 {
+            passes
+                .iter()
+                .map(|pass| pass.set.tsince - pass.rise.tsince)
+                .sum()
+        };
+        assert!(duration(&passes) < duration(&open));
+    }
+}
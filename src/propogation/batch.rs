@@ -0,0 +1,648 @@
+use crate::constants::*;
+use crate::io::satnum::CatalogNumber;
+use crate::io::Satrec;
+use crate::propogation::initl::InitlMethod;
+use crate::propogation::sgp4::*;
+use crate::Vec3;
+
+use crate::time::{Epoch, TimeScale};
+use std::convert::TryInto;
+use std::f64::consts::FRAC_2_PI;
+
+#[derive(Debug, PartialEq)]
+pub enum BatchError {
+    /// The number of times does not match the number of objects (times, objects).
+    LengthMismatch(usize, usize),
+}
+
+/// Number of objects propagated together by the near-earth kernel.
+const LANES: usize = 8;
+
+/// Near-earth elements of many objects, packed as one array per field.
+///
+/// Holds what the near-earth SGP4 equations read after initialization, plus the terms
+/// that only depend on the elements, so the kernel streams through contiguous arrays of
+/// `f64` instead of whole `Satrec`s. Arrays are padded to a multiple of `LANES`.
+#[derive(Debug, Clone, Default)]
+struct NearEarthElements {
+    index: Vec<usize>,
+    jdsatepoch: Vec<f64>,
+
+    mo: Vec<f64>,
+    mdot: Vec<f64>,
+    argpo: Vec<f64>,
+    argpdot: Vec<f64>,
+    nodeo: Vec<f64>,
+    nodedot: Vec<f64>,
+    nodecf: Vec<f64>,
+    cc1: Vec<f64>,
+    bstar_cc4: Vec<f64>,
+    t2cof: Vec<f64>,
+
+    // false for simplified drag (isimp == 1), which leaves out the terms below.
+    full_drag: Vec<bool>,
+    omgcof: Vec<f64>,
+    eta: Vec<f64>,
+    xmcof: Vec<f64>,
+    delmo: Vec<f64>,
+    d2: Vec<f64>,
+    d3: Vec<f64>,
+    d4: Vec<f64>,
+    bstar_cc5: Vec<f64>,
+    sinmao: Vec<f64>,
+    t3cof: Vec<f64>,
+    t4cof: Vec<f64>,
+    t5cof: Vec<f64>,
+
+    no: Vec<f64>,
+    ecco: Vec<f64>,
+    inclo: Vec<f64>,
+    sinio: Vec<f64>,
+    cosio: Vec<f64>,
+    aycof: Vec<f64>,
+    xlcof: Vec<f64>,
+    con41: Vec<f64>,
+    x1mth2: Vec<f64>,
+    x7thm1: Vec<f64>,
+
+    xke: Vec<f64>,
+    j2: Vec<f64>,
+    earth_radius: Vec<f64>,
+    vkmpersec: Vec<f64>,
+    // (xke / no) ^ 2/3
+    ao: Vec<f64>,
+}
+
+impl NearEarthElements {
+    fn push(&mut self, satrec: &Satrec) {
+        let GravityConstants {
+            xke,
+            j2,
+            earth_radius,
+            ..
+        } = satrec.gravity_model.constants();
+
+        self.jdsatepoch.push(satrec.jdsatepoch);
+
+        self.mo.push(satrec.mo);
+        self.mdot.push(satrec.mdot);
+        self.argpo.push(satrec.argpo);
+        self.argpdot.push(satrec.argpdot);
+        self.nodeo.push(satrec.nodeo);
+        self.nodedot.push(satrec.nodedot);
+        self.nodecf.push(satrec.nodecf);
+        self.cc1.push(satrec.cc1);
+        self.bstar_cc4.push(satrec.bstar * satrec.cc4);
+        self.t2cof.push(satrec.t2cof);
+
+        self.full_drag.push(satrec.isimp != 1);
+        self.omgcof.push(satrec.omgcof);
+        self.eta.push(satrec.eta);
+        self.xmcof.push(satrec.xmcof);
+        self.delmo.push(satrec.delmo);
+        self.d2.push(satrec.d2);
+        self.d3.push(satrec.d3);
+        self.d4.push(satrec.d4);
+        self.bstar_cc5.push(satrec.bstar * satrec.cc5);
+        self.sinmao.push(satrec.sinmao);
+        self.t3cof.push(satrec.t3cof);
+        self.t4cof.push(satrec.t4cof);
+        self.t5cof.push(satrec.t5cof);
+
+        self.no.push(satrec.no);
+        self.ecco.push(satrec.ecco);
+        self.inclo.push(satrec.inclo);
+        self.sinio.push(satrec.inclo.sin());
+        self.cosio.push(satrec.inclo.cos());
+        self.aycof.push(satrec.aycof);
+        self.xlcof.push(satrec.xlcof);
+        self.con41.push(satrec.con41);
+        self.x1mth2.push(satrec.x1mth2);
+        self.x7thm1.push(satrec.x7thm1);
+
+        self.xke.push(xke);
+        self.j2.push(j2);
+        self.earth_radius.push(earth_radius);
+        self.vkmpersec.push((earth_radius * xke) / 60.0);
+        self.ao.push((xke / satrec.no).powf(X2O3));
+    }
+}
+
+/// Propagated states of a batch, one entry per field and object in input order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BatchResults {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+    pub vx: Vec<f64>,
+    pub vy: Vec<f64>,
+    pub vz: Vec<f64>,
+
+    /// SGP4 error code of each object, 0 when propagation succeeded.
+    pub error: Vec<u8>,
+}
+
+impl BatchResults {
+    fn with_len(len: usize) -> BatchResults {
+        BatchResults {
+            x: vec![0.0; len],
+            y: vec![0.0; len],
+            z: vec![0.0; len],
+            vx: vec![0.0; len],
+            vy: vec![0.0; len],
+            vz: vec![0.0; len],
+            error: vec![0; len],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.error.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.error.is_empty()
+    }
+
+    /// Result of the object at `index`, in the form `sgp4` returns it.
+    pub fn get(&self, index: usize) -> Result<SGP4Result, SGP4Error> {
+        match self.error[index] {
+            0 => Ok(SGP4Result {
+                position: Vec3 {
+                    x: self.x[index],
+                    y: self.y[index],
+                    z: self.z[index],
+                },
+                velocity: Vec3 {
+                    x: self.vx[index],
+                    y: self.vy[index],
+                    z: self.vz[index],
+                },
+            }),
+            1 => Err(SGP4Error::Eccentricty),
+            2 => Err(SGP4Error::Nm),
+            3 => Err(SGP4Error::Ep),
+            4 => Err(SGP4Error::Pl),
+            _ => Err(SGP4Error::DecayCondition),
+        }
+    }
+
+    fn set(&mut self, index: usize, result: Result<SGP4Result, SGP4Error>) {
+        match result {
+            Ok(SGP4Result { position, velocity }) => {
+                self.x[index] = position.x;
+                self.y[index] = position.y;
+                self.z[index] = position.z;
+                self.vx[index] = velocity.x;
+                self.vy[index] = velocity.y;
+                self.vz[index] = velocity.z;
+                self.error[index] = 0;
+            }
+            Err(err) => self.error[index] = err.code(),
+        }
+    }
+}
+
+/// Many initialized satrecs packed for propagation to a common time.
+///
+/// Near-earth objects go through a kernel that works on `LANES` objects at a time from
+/// structure-of-arrays storage, written so the compiler can vectorize every step. Its
+/// results agree with [`sgp4`] to about 1e-11 relative (well below a millimeter for
+/// orbiting objects) for times within years of epoch. Deep-space objects are propagated
+/// one by one with [`sgp4_with_cache`].
+#[derive(Clone)]
+pub struct SatrecBatch {
+    satnum: Vec<CatalogNumber>,
+    near_earth: NearEarthElements,
+    deep_space: Vec<(usize, Satrec)>,
+}
+
+impl SatrecBatch {
+    pub fn new(satrecs: &[Satrec]) -> SatrecBatch {
+        let mut near_earth = NearEarthElements::default();
+        let mut deep_space = vec![];
+
+        for (index, satrec) in satrecs.iter().enumerate() {
+            if satrec.method == InitlMethod::D {
+                deep_space.push((index, satrec.clone()));
+            } else {
+                near_earth.index.push(index);
+                near_earth.push(satrec);
+            }
+        }
+
+        // pad the last chunk with copies of the last object; their results are dropped.
+        if let Some(&last) = near_earth.index.last() {
+            while near_earth.jdsatepoch.len() % LANES != 0 {
+                near_earth.push(&satrecs[last]);
+            }
+        }
+
+        SatrecBatch {
+            satnum: satrecs.iter().map(|satrec| satrec.satnum).collect(),
+            near_earth,
+            deep_space,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.satnum.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.satnum.is_empty()
+    }
+
+    /// Catalog number of the object at `index`.
+    pub fn satnum(&self, index: usize) -> CatalogNumber {
+        self.satnum[index]
+    }
+
+    /// Propogate each object to its own time since epoch (minutes), given in input order.
+    ///
+    /// There must be one time per object.
+    pub fn propogate(&self, tsince: &[f64]) -> Result<BatchResults, BatchError> {
+        if tsince.len() != self.len() {
+            return Err(BatchError::LengthMismatch(tsince.len(), self.len()));
+        }
+
+        let near_tsince = self
+            .near_earth
+            .index
+            .iter()
+            .map(|&index| tsince[index])
+            .collect::<Vec<f64>>();
+
+        Ok(self.run(near_tsince, |index, _| tsince[index]))
+    }
+
+    /// Propogate every object to the same time, a UTC `DateTime` or an `Epoch`.
+    pub fn propogate_datetime<T: Into<Epoch>>(&self, time: T) -> BatchResults {
+        let (whole, fraction) = time.into().to_scale(TimeScale::Utc).jd_split();
+
+        let near_tsince = self
+            .near_earth
+            .jdsatepoch
+            .iter()
+            .map(|jdsatepoch| ((whole - jdsatepoch) + fraction) * MINUTES_PER_DAY)
+            .collect::<Vec<f64>>();
+
+        self.run(near_tsince, |_, satrec| ((whole - satrec.jdsatepoch) + fraction) * MINUTES_PER_DAY)
+    }
+
+    fn run<F: Fn(usize, &Satrec) -> f64>(&self, mut near_tsince: Vec<f64>, deep_tsince: F) -> BatchResults {
+        let mut results = BatchResults::with_len(self.len());
+
+        near_tsince.resize(self.near_earth.jdsatepoch.len(), 0.0);
+        for start in (0..near_tsince.len()).step_by(LANES) {
+            near_earth_kernel(&self.near_earth, start, lanes(&near_tsince, start), &mut results);
+        }
+
+        for (index, satrec) in &self.deep_space {
+            results.set(*index, sgp4_with_cache(satrec, deep_tsince(*index, satrec), None));
+        }
+
+        results
+    }
+}
+
+fn lanes<T>(values: &[T], start: usize) -> &[T; LANES] {
+    values[start..start + LANES].try_into().unwrap()
+}
+
+const PIO2_1: f64 = 1.570_796_326_734_125_6;
+const PIO2_2: f64 = 6.077_100_506_303_966e-11;
+const PIO2_3: f64 = 2.022_266_248_711_166_5e-21;
+
+const S1: f64 = -1.666_666_666_666_663_2e-1;
+const S2: f64 = 8.333_333_333_322_49e-3;
+const S3: f64 = -1.984_126_982_985_795e-4;
+const S4: f64 = 2.755_731_370_707_006_8e-6;
+const S5: f64 = -2.505_076_025_340_686_3e-8;
+const S6: f64 = 1.589_690_995_211_55e-10;
+
+const C1: f64 = 4.166_666_666_666_66e-2;
+const C2: f64 = -1.388_888_888_887_411e-3;
+const C3: f64 = 2.480_158_728_947_673e-5;
+const C4: f64 = -2.755_731_435_139_066_3e-7;
+const C5: f64 = 2.087_572_321_298_175e-9;
+const C6: f64 = -1.135_964_755_778_819_5e-11;
+
+/// Sine and cosine without branches or library calls, so loops over lanes vectorize.
+///
+/// Reduces by multiples of π/2 in three parts and evaluates the fdlibm polynomials on
+/// [-π/4, π/4]. Within an ulp or two of `f64::sin_cos` for |x| below about 1e6.
+#[inline(always)]
+fn sin_cos(x: f64) -> (f64, f64) {
+    // adding 1.5 * 2^52 rounds to an integer held in the low bits of the mantissa.
+    const ROUND: f64 = 6_755_399_441_055_744.0;
+    let shifted = (x * FRAC_2_PI) + ROUND;
+    let k = shifted - ROUND;
+    let quadrant = shifted.to_bits() & 3;
+
+    let r = ((x - (k * PIO2_1)) - (k * PIO2_2)) - (k * PIO2_3);
+    let z = r * r;
+    let s = r + (r * z * (S1 + (z * (S2 + (z * (S3 + (z * (S4 + (z * (S5 + (z * S6)))))))))));
+    let c = (1.0 - (0.5 * z))
+        + (z * z * (C1 + (z * (C2 + (z * (C3 + (z * (C4 + (z * (C5 + (z * C6)))))))))));
+
+    let (sin, cos) = if quadrant & 1 == 1 { (c, s) } else { (s, c) };
+    let sin = if quadrant & 2 == 2 { -sin } else { sin };
+    let cos = if (quadrant + 1) & 2 == 2 { -cos } else { cos };
+    (sin, cos)
+}
+
+/// `x % TWO_PI` without the fmod library call; exact to rounding for the angles here.
+#[inline(always)]
+fn rem_two_pi(x: f64) -> f64 {
+    x - (TWO_PI * ((x / TWO_PI) as i64 as f64))
+}
+
+/// Near-earth SGP4 for the `LANES` packed objects starting at `start`.
+///
+/// Follows `sgp4_with_cache` step by step, each step a loop over the lanes. Branches
+/// become selects, the 2/3 power of the mean motion is taken from the packed elements
+/// and the argument of latitude is rotated rather than recovered with atan2. Lanes stop
+/// the kepler iteration when they converge as in the scalar code.
+fn near_earth_kernel(el: &NearEarthElements, start: usize, tsince: &[f64; LANES], results: &mut BatchResults) {
+    let mo = lanes(&el.mo, start);
+    let mdot = lanes(&el.mdot, start);
+    let argpo = lanes(&el.argpo, start);
+    let argpdot = lanes(&el.argpdot, start);
+    let nodeo = lanes(&el.nodeo, start);
+    let nodedot = lanes(&el.nodedot, start);
+    let nodecf = lanes(&el.nodecf, start);
+    let cc1 = lanes(&el.cc1, start);
+    let bstar_cc4 = lanes(&el.bstar_cc4, start);
+    let t2cof = lanes(&el.t2cof, start);
+    let full_drag = lanes(&el.full_drag, start);
+    let omgcof = lanes(&el.omgcof, start);
+    let eta = lanes(&el.eta, start);
+    let xmcof = lanes(&el.xmcof, start);
+    let delmo = lanes(&el.delmo, start);
+    let d2 = lanes(&el.d2, start);
+    let d3 = lanes(&el.d3, start);
+    let d4 = lanes(&el.d4, start);
+    let bstar_cc5 = lanes(&el.bstar_cc5, start);
+    let sinmao = lanes(&el.sinmao, start);
+    let t3cof = lanes(&el.t3cof, start);
+    let t4cof = lanes(&el.t4cof, start);
+    let t5cof = lanes(&el.t5cof, start);
+    let no = lanes(&el.no, start);
+    let ecco = lanes(&el.ecco, start);
+    let inclo = lanes(&el.inclo, start);
+    let sinio = lanes(&el.sinio, start);
+    let cosio = lanes(&el.cosio, start);
+    let aycof = lanes(&el.aycof, start);
+    let xlcof = lanes(&el.xlcof, start);
+    let con41 = lanes(&el.con41, start);
+    let x1mth2 = lanes(&el.x1mth2, start);
+    let x7thm1 = lanes(&el.x7thm1, start);
+    let xke = lanes(&el.xke, start);
+    let j2 = lanes(&el.j2, start);
+    let earth_radius = lanes(&el.earth_radius, start);
+    let vkmpersec = lanes(&el.vkmpersec, start);
+    let ao = lanes(&el.ao, start);
+
+    let mut error = [0u8; LANES];
+
+    //  ------- update for secular gravity and atmospheric drag -----
+    let mut am = [0.0; LANES];
+    let mut nm = [0.0; LANES];
+    let mut em = [0.0; LANES];
+    let mut mm = [0.0; LANES];
+    let mut argpm = [0.0; LANES];
+    let mut nodem = [0.0; LANES];
+    for l in 0..LANES {
+        let t = tsince[l];
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let t4 = t3 * t;
+        let xmdf = mo[l] + (mdot[l] * t);
+        let argpdf = argpo[l] + (argpdot[l] * t);
+        nodem[l] = nodeo[l] + (nodedot[l] * t) + (nodecf[l] * t2);
+
+        let delomg = omgcof[l] * t;
+        let delmtemp = 1.0 + (eta[l] * sin_cos(xmdf).1);
+        let delm = xmcof[l] * ((delmtemp * delmtemp * delmtemp) - delmo[l]);
+        let temp = delomg + delm;
+        mm[l] = if full_drag[l] { xmdf + temp } else { xmdf };
+        argpm[l] = if full_drag[l] { argpdf - temp } else { argpdf };
+
+        let simple_tempa = 1.0 - (cc1[l] * t);
+        let simple_tempe = bstar_cc4[l] * t;
+        let simple_templ = t2cof[l] * t2;
+        let (tempa, tempe, templ) = if full_drag[l] {
+            (
+                simple_tempa - (d2[l] * t2) - (d3[l] * t3) - (d4[l] * t4),
+                simple_tempe + (bstar_cc5[l] * (sin_cos(mm[l]).0 - sinmao[l])),
+                simple_templ + (t3cof[l] * t3) + (t4 * (t4cof[l] + (t * t5cof[l]))),
+            )
+        } else {
+            (simple_tempa, simple_tempe, simple_templ)
+        };
+
+        am[l] = ao[l] * tempa * tempa;
+        nm[l] = xke[l] / (am[l] * am[l].sqrt());
+        em[l] = ecco[l] - tempe;
+        mm[l] += no[l] * templ;
+    }
+
+    for l in 0..LANES {
+        error[l] = if no[l] <= 0.0 {
+            SGP4Error::Nm.code()
+        } else if em[l] >= 1.0 || em[l] < -0.001 {
+            SGP4Error::Eccentricty.code()
+        } else {
+            0
+        };
+
+        //  sgp4fix fix tolerance to avoid a divide by zero
+        em[l] = em[l].max(1.0e-6);
+    }
+
+    //  -------------------- long period periodics ------------------
+    let mut axnl = [0.0; LANES];
+    let mut aynl = [0.0; LANES];
+    let mut u = [0.0; LANES];
+    for l in 0..LANES {
+        let xlm = rem_two_pi(mm[l] + argpm[l] + nodem[l]);
+        nodem[l] = rem_two_pi(nodem[l]);
+        argpm[l] = rem_two_pi(argpm[l]);
+        mm[l] = rem_two_pi(xlm - argpm[l] - nodem[l]);
+
+        let (sinargp, cosargp) = sin_cos(argpm[l]);
+        axnl[l] = em[l] * cosargp;
+        let temp = 1.0 / (am[l] * (1.0 - (em[l] * em[l])));
+        aynl[l] = (em[l] * sinargp) + (temp * aycof[l]);
+        let xl = mm[l] + argpm[l] + nodem[l] + (temp * xlcof[l] * axnl[l]);
+        u[l] = rem_two_pi(xl - nodem[l]);
+    }
+
+    // --------------------- solve kepler's equation ---------------
+    let mut eo1 = u;
+    let mut sineo1 = [0.0; LANES];
+    let mut coseo1 = [0.0; LANES];
+    let mut active = [true; LANES];
+    for _ in 0..10 {
+        if !active.iter().any(|&a| a) {
+            break;
+        }
+
+        for l in 0..LANES {
+            let (s, c) = sin_cos(eo1[l]);
+            let mut tem5 = 1.0 - (c * axnl[l]) - (s * aynl[l]);
+            tem5 = (((u[l] - (aynl[l] * c)) + (axnl[l] * s)) - eo1[l]) / tem5;
+            tem5 = tem5.clamp(-0.95, 0.95);
+
+            if active[l] {
+                sineo1[l] = s;
+                coseo1[l] = c;
+                eo1[l] += tem5;
+            }
+            active[l] = active[l] && tem5.abs() >= 1.0e-12;
+        }
+    }
+
+    //  ------------- short period preliminary quantities -----------
+    for l in 0..LANES {
+        let ecose = (axnl[l] * coseo1[l]) + (aynl[l] * sineo1[l]);
+        let esine = (axnl[l] * sineo1[l]) - (aynl[l] * coseo1[l]);
+        let el2 = (axnl[l] * axnl[l]) + (aynl[l] * aynl[l]);
+        let pl = am[l] * (1.0 - el2);
+        if error[l] == 0 && pl < 0.0 {
+            error[l] = SGP4Error::Pl.code();
+        }
+
+        let rl = am[l] * (1.0 - ecose);
+        let rdotl = (am[l].sqrt() * esine) / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = (am[l] / rl) * (sineo1[l] - aynl[l] - (axnl[l] * temp));
+        let cosu = (am[l] / rl) * ((coseo1[l] - axnl[l]) + (aynl[l] * temp));
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - (2.0 * sinu * sinu);
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * j2[l] * temp;
+        let temp2 = temp1 * temp;
+
+        // -------------- update for short period periodics ------------
+        let mrt = (rl * (1.0 - (1.5 * temp2 * betal * con41[l]))) + (0.5 * temp1 * x1mth2[l] * cos2u);
+        let xnode = nodem[l] + (1.5 * temp2 * cosio[l] * sin2u);
+        let xinc = inclo[l] + (1.5 * temp2 * cosio[l] * sinio[l] * cos2u);
+        let mvt = rdotl - ((nm[l] * temp1 * x1mth2[l] * sin2u) / xke[l]);
+        let rvdot = rvdotl + ((nm[l] * temp1 * ((x1mth2[l] * cos2u) + (1.5 * con41[l]))) / xke[l]);
+
+        // su = atan2(sinu, cosu) - dsu, as a rotation of (cosu, sinu).
+        let dsu = 0.25 * temp2 * x7thm1[l] * sin2u;
+        let (sindsu, cosdsu) = sin_cos(dsu);
+        let norm = 1.0 / ((sinu * sinu) + (cosu * cosu)).sqrt();
+        let sinsu = ((sinu * cosdsu) - (cosu * sindsu)) * norm;
+        let cossu = ((cosu * cosdsu) + (sinu * sindsu)) * norm;
+
+        // --------------------- orientation vectors -------------------
+        let (snod, cnod) = sin_cos(xnode);
+        let (sini, cosi) = sin_cos(xinc);
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = (xmx * sinsu) + (cnod * cossu);
+        let uy = (xmy * sinsu) + (snod * cossu);
+        let uz = sini * sinsu;
+        let vx = (xmx * cossu) - (cnod * sinsu);
+        let vy = (xmy * cossu) - (snod * sinsu);
+        let vz = sini * cossu;
+
+        // sgp4fix for decaying satellites
+        if error[l] == 0 && mrt < 1.0 {
+            error[l] = SGP4Error::DecayCondition.code();
+        }
+
+        // --------- position and velocity (in km and km/sec) ----------
+        if let Some(&index) = el.index.get(start + l) {
+            results.error[index] = error[l];
+            results.x[index] = (mrt * ux) * earth_radius[l];
+            results.y[index] = (mrt * uy) * earth_radius[l];
+            results.z[index] = (mrt * uz) * earth_radius[l];
+            results.vx[index] = ((mvt * ux) + (rvdot * vx)) * vkmpersec[l];
+            results.vy[index] = ((mvt * uy) + (rvdot * vy)) * vkmpersec[l];
+            results.vz[index] = ((mvt * uz) + (rvdot * vz)) * vkmpersec[l];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_diff, verification_satrecs};
+    use chrono::{TimeZone, Utc};
+
+    fn assert_matches(result: Result<SGP4Result, SGP4Error>, expected: Result<SGP4Result, SGP4Error>) {
+        match (result, expected) {
+            (Ok(result), Ok(expected)) => {
+                let r = expected.position.norm();
+                let v = expected.velocity.range(&Vec3 { x: 0.0, y: 0.0, z: 0.0 });
+
+                // past its decay 29141 runs off to meaningless distances.
+                if r > 1.0e6 {
+                    return;
+                }
+
+                assert!(result.position.range(&expected.position) < 1e-11 * r);
+                assert!(result.velocity.range(&expected.velocity) < 1e-11 * v);
+            }
+            (result, expected) => assert_eq!(result, expected),
+        }
+    }
+
+    #[test]
+    fn test_sin_cos() {
+        let mut x = -1.0e4;
+        while x < 1.0e4 {
+            let (sin, cos) = sin_cos(x);
+            assert_diff(sin, x.sin(), 1e-15);
+            assert_diff(cos, x.cos(), 1e-15);
+            x += 0.0137;
+        }
+
+        assert_eq!(sin_cos(0.0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_matches_sgp4() {
+        let satrecs = verification_satrecs();
+        let batch = SatrecBatch::new(&satrecs);
+        assert_eq!(batch.len(), satrecs.len());
+
+        for t in [-1440.0, 0.0, 90.0, 1440.0, 4320.0] {
+            let results = batch.propogate(&vec![t; satrecs.len()]).unwrap();
+
+            for (index, satrec) in satrecs.iter().enumerate() {
+                assert_eq!(batch.satnum(index), satrec.satnum);
+                assert_matches(results.get(index), sgp4_with_cache(satrec, t, None));
+            }
+        }
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let satrecs = verification_satrecs();
+        let batch = SatrecBatch::new(&satrecs);
+
+        assert_eq!(
+            batch.propogate(&[0.0]).err(),
+            Some(BatchError::LengthMismatch(1, satrecs.len()))
+        );
+    }
+
+    #[test]
+    fn test_datetime() {
+        let satrecs = verification_satrecs();
+        let batch = SatrecBatch::new(&satrecs);
+        let time = Utc.with_ymd_and_hms(2006, 6, 25, 12, 0, 0).unwrap();
+
+        let results = batch.propogate_datetime(time);
+        for (index, satrec) in satrecs.iter().enumerate() {
+            assert_matches(results.get(index), crate::propogation::propogate_datetime(satrec, time));
+        }
+    }
+}
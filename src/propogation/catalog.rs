@@ -0,0 +1,78 @@
+use crate::io::satnum::CatalogNumber;
+use crate::io::Satrec;
+use crate::propogation::propogate_datetime;
+use crate::propogation::sgp4::{SGP4Error, SGP4Result};
+
+use crate::time::Epoch;
+use std::collections::BTreeMap;
+
+/// Propagation results of a catalog, keyed by catalog number.
+pub type CatalogResults = BTreeMap<CatalogNumber, Result<SGP4Result, SGP4Error>>;
+
+/// Propogate every satrec of a catalog to the same time, a UTC `DateTime` or an `Epoch`.
+///
+/// When a catalog number appears more than once, the result of the last satrec with
+/// that number is kept.
+pub fn propogate_catalog<T: Into<Epoch>>(satrecs: &[Satrec], time: T) -> CatalogResults {
+    let time = time.into();
+    satrecs
+        .iter()
+        .map(|satrec| (satrec.satnum, propogate_datetime(satrec, time)))
+        .collect()
+}
+
+/// Propogate every satrec of a catalog to the same time, spread across the rayon thread pool.
+///
+/// Results are identical to [`propogate_catalog`] whatever the number of threads,
+/// including which satrec wins for duplicated catalog numbers.
+#[cfg(feature = "rayon")]
+pub fn par_propogate_catalog<T: Into<Epoch>>(satrecs: &[Satrec], time: T) -> CatalogResults {
+    use rayon::prelude::*;
+
+    let time = time.into();
+
+    // collecting into a vec keeps the input order, so duplicates resolve as in the serial version.
+    satrecs
+        .par_iter()
+        .map(|satrec| (satrec.satnum, propogate_datetime(satrec, time)))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::verification_satrecs;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_catalog() {
+        let satrecs = verification_satrecs();
+        let time = Utc.with_ymd_and_hms(2006, 6, 25, 12, 0, 0).unwrap();
+
+        let results = propogate_catalog(&satrecs, time);
+        assert_eq!(results.len(), satrecs.len());
+
+        for satrec in &satrecs {
+            assert_eq!(results[&satrec.satnum], propogate_datetime(satrec, time));
+        }
+
+        // 28872 decays within an hour of its 2005 epoch.
+        assert!(results[&CatalogNumber(28872)].is_err());
+        assert!(results[&CatalogNumber(5)].is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_par_catalog() {
+        let satrecs = verification_satrecs();
+        let time = Utc.with_ymd_and_hms(2006, 6, 25, 12, 0, 0).unwrap();
+        let expected = propogate_catalog(&satrecs, time);
+
+        for threads in [1, 2, 8] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            assert_eq!(pool.install(|| par_propogate_catalog(&satrecs, time)), expected);
+        }
+    }
+}
@@ -17,9 +17,13 @@ pub enum DpperInit {
     N,
 }
 
-#[derive(PartialEq, Clone)]
+/// SGP4 operation mode.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
 pub enum DpperOpsMode {
+    /// AFSPC compatible: the original sidereal time calculation and nodal wrapping.
     A,
+    /// Improved: modern sidereal time calculation.
+    #[default]
     I,
     UNDEFINED,
 }
@@ -28,6 +32,9 @@ pub struct DpperOptions {
     pub init: DpperInit,
     pub opsmode: DpperOpsMode,
 
+    /// Time since epoch (minutes).
+    pub t: f64,
+
     pub inclo: f64,
     pub ep: f64,
     pub inclp: f64,
@@ -122,7 +129,7 @@ pub fn dpper(satrec: &Satrec, options: DpperOptions) -> DpperResult {
     let sl2 = satrec.sl2;
     let sl3 = satrec.sl3;
     let sl4 = satrec.sl4;
-    let t = satrec.t;
+    let t = options.t;
     let xgh2 = satrec.xgh2;
     let xgh3 = satrec.xgh3;
     let xgh4 = satrec.xgh4;
@@ -294,6 +301,7 @@ pub fn dpper(satrec: &Satrec, options: DpperOptions) -> DpperResult {
 
 #[cfg(test)]
 mod test {
+    use crate::io::satnum::CatalogNumber;
     use crate::io::Satrec;
     use crate::propogation::dpper::*;
     use crate::propogation::initl::*;
@@ -304,7 +312,12 @@ mod test {
         let satrec = Satrec {
             name: None,
             error: 0,
-            satnum: "11801".to_string(),
+            satnum: CatalogNumber(11801),
+            classification: 'U',
+            intldesg: None,
+            ephtype: 0,
+            elnum: 1,
+            revnum: 1,
             epochyr: 80,
             epochdays: 230.29629788,
             ndot: 4.3363644592306274e-8,
@@ -316,6 +329,7 @@ mod test {
             argpo: 0.8285461931652521,
             mo: 0.1817184457298936,
             no: 0.009971131594572634,
+            no_kozai: 0.009971844782555844,
             a: 3.817121025708788,
             alta: 5.610503933958173,
             altp: 0.023738117459404462,
@@ -404,10 +418,12 @@ mod test {
             xli: 0.0,
             xni: 0.0,
             operationmode: DpperOpsMode::I,
+            gravity_model: crate::constants::GravityModel::Wgs84,
             init: DpperInit::Y,
         };
 
         let opts = DpperOptions {
+            t: 0.0,
             inclo: 0.8166674822761788,
             init: DpperInit::Y,
             ep: 0.7318036,
@@ -432,7 +448,12 @@ mod test {
         let satrec = Satrec {
             name: None,
             error: 0,
-            satnum: "11801".to_string(),
+            satnum: CatalogNumber(11801),
+            classification: 'U',
+            intldesg: None,
+            ephtype: 0,
+            elnum: 1,
+            revnum: 1,
             epochyr: 80,
             epochdays: 230.29629788,
             ndot: 4.3363644592306274e-8,
@@ -444,6 +465,7 @@ mod test {
             argpo: 0.8285461931652521,
             mo: 0.1817184457298936,
             no: 0.009971131594572634,
+            no_kozai: 0.009971844782555844,
             a: 3.817121025708788,
             alta: 5.610503933958173,
             altp: 0.023738117459404462,
@@ -532,10 +554,12 @@ mod test {
             xli: 0.0,
             xni: 0.0,
             operationmode: DpperOpsMode::I,
+            gravity_model: crate::constants::GravityModel::Wgs84,
             init: DpperInit::N,
         };
 
         let opts = DpperOptions {
+            t: 0.0,
             inclo: 0.8166674822761788,
             init: DpperInit::N,
             ep: 0.7318036,
@@ -554,4 +578,41 @@ mod test {
         assert_eq!(res.argpp, 0.829566024830811);
         assert_similar(res.mp, 0.1808079384635203);
     }
+
+    #[test]
+    fn test_afspc_nodal_wrap() {
+        let satrec = crate::io::twoline2satrec(
+            "1 11801U          80230.29629788  .01431103  00000-0  14311-1        ",
+            "2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848      ",
+        )
+        .unwrap();
+
+        // Below 0.2 rad of inclination the lyddane modification is used, where AFSPC
+        // mode keeps the node in [0, 2pi).
+        let options = |opsmode| DpperOptions {
+            t: satrec.t,
+            inclo: satrec.inclo,
+            init: DpperInit::N,
+            ep: 0.7318036,
+            inclp: 0.1,
+            nodep: -0.5,
+            argpp: 0.8285461931652521,
+            mp: 0.18171844572989393,
+            opsmode,
+        };
+
+        let improved = dpper(&satrec, options(DpperOpsMode::I));
+        let afspc = dpper(&satrec, options(DpperOpsMode::A));
+
+        assert!(improved.nodep < 0.0);
+        assert!(afspc.nodep >= 0.0 && afspc.nodep < TWO_PI);
+        crate::tests::assert_diff(afspc.nodep, improved.nodep + TWO_PI, 1e-12);
+        // The unwrapped node also feeds the argument of perigee, which is where the
+        // AFSPC results really differ.
+        assert!(afspc.argpp != improved.argpp);
+        crate::tests::assert_diff(afspc.argpp, improved.argpp, 1e-3);
+        assert_eq!(afspc.ep, improved.ep);
+        assert_eq!(afspc.inclp, improved.inclp);
+        assert_eq!(afspc.mp, improved.mp);
+    }
 }
@@ -1,6 +1,7 @@
 use crate::constants::*;
 
 pub struct DsinitOptions {
+    pub xke: f64,
     pub cosim: f64,
     pub argpo: f64,
     pub s1: f64,
@@ -118,6 +119,7 @@ pub struct DinitResult {
 *  author        : david vallado                  719-573-2600   28 jun 2005
 *
 *  inputs        :
+*    xke         - reciprocal of tumin
 *    cosim, sinim-
 *    emsq        - eccentricity squared
 *    argpo       - argument of perigee
@@ -189,6 +191,7 @@ pub struct DinitResult {
 ----------------------------------------------------------------------------*/
 
 pub fn dsinit(options: DsinitOptions) -> DinitResult {
+    let xke = options.xke;
     let cosim = options.cosim;
     let argpo = options.argpo;
     let s1 = options.s1;
@@ -369,7 +372,7 @@ pub fn dsinit(options: DsinitOptions) -> DinitResult {
 
     // -------------- initialize the resonance terms -------------
     if irez != 0.0 {
-        aonv = (nm / XKE).powf(X2O3);
+        aonv = (nm / xke).powf(X2O3);
 
         // ---------- geopotential resonance for 12 hour orbits ------
         if irez == 2.0 {
@@ -530,6 +533,7 @@ mod tests {
     #[test]
     fn test_dsinit() {
         let opts = DsinitOptions {
+            xke: crate::constants::XKE,
             cosim: 0.6846539709541596,
             emsq: 0.53553650897296,
             argpo: 0.8285461931652521,
@@ -0,0 +1,205 @@
+use crate::io::Satrec;
+use crate::propogation::sgp4::*;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// A single step of an ephemeris.
+#[derive(Debug, PartialEq)]
+pub struct EphemerisEntry {
+    /// Time of the step.
+    pub time: DateTime<Utc>,
+
+    /// Time of the step, in minutes since the satrec epoch.
+    pub tsince: f64,
+
+    /// TEME position and velocity at the step, or the propagation error.
+    pub state: Result<SGP4Result, SGP4Error>,
+}
+
+/// Iterator over the propagated states of a satrec at fixed steps between two times.
+///
+/// Both ends are inclusive when the range is a whole number of steps. Steps continue
+/// after a propagation error so each yields its own result. Deep-space integrator state
+/// is kept between steps, so consecutive resonant steps do not integrate from epoch.
+pub struct Ephemeris<'a> {
+    satrec: &'a Satrec,
+    cache: IntegratorCache,
+    epoch: DateTime<Utc>,
+    start: Duration,
+    /// Step in nanoseconds.
+    step: i64,
+    index: i64,
+    count: i64,
+}
+
+fn minutes(duration: Duration) -> f64 {
+    match duration.num_nanoseconds() {
+        Some(nanos) => nanos as f64 / 60e9,
+        None => duration.num_milliseconds() as f64 / 60e3,
+    }
+}
+
+fn from_minutes(minutes: f64) -> Duration {
+    Duration::nanoseconds((minutes * 60e9).round() as i64)
+}
+
+impl<'a> Ephemeris<'a> {
+    /// Ephemeris from `start` to `stop` minutes since epoch, every `step` minutes.
+    ///
+    /// A negative step runs backwards. Nothing is yielded when the step is zero or does
+    /// not lead from `start` towards `stop`.
+    pub fn new(satrec: &'a Satrec, start: f64, stop: f64, step: f64) -> Ephemeris<'a> {
+        Ephemeris::with_offsets(satrec, from_minutes(start), from_minutes(stop), from_minutes(step))
+    }
+
+    /// Ephemeris between two times, every `step`.
+    pub fn from_datetimes(
+        satrec: &'a Satrec,
+        start: DateTime<Utc>,
+        stop: DateTime<Utc>,
+        step: Duration,
+    ) -> Ephemeris<'a> {
+        let epoch = satrec.epoch();
+        Ephemeris::with_offsets(satrec, start - epoch, stop - epoch, step)
+    }
+
+    fn with_offsets(satrec: &'a Satrec, start: Duration, stop: Duration, step: Duration) -> Ephemeris<'a> {
+        let span = minutes(stop - start);
+        let step_minutes = minutes(step);
+        let step = match step.num_nanoseconds() {
+            Some(nanos) => nanos,
+            None if step < Duration::zero() => -i64::MAX,
+            None => i64::MAX,
+        };
+
+        let count = if step_minutes == 0.0 || span / step_minutes < 0.0 {
+            0
+        } else {
+            // allow for rounding when the range is a whole number of steps.
+            let count = (span / step_minutes + 1e-9).floor() as i64 + 1;
+
+            // every offset from the start must fit in nanoseconds.
+            count.min(i64::MAX / step.abs() + 1)
+        };
+
+        Ephemeris {
+            satrec,
+            cache: IntegratorCache::default(),
+            epoch: satrec.epoch(),
+            start,
+            step,
+            index: 0,
+            count,
+        }
+    }
+}
+
+impl<'a> Iterator for Ephemeris<'a> {
+    type Item = EphemerisEntry;
+
+    fn next(&mut self) -> Option<EphemerisEntry> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let offset = self.start + Duration::nanoseconds(self.step * self.index);
+        self.index += 1;
+
+        let tsince = minutes(offset);
+
+        Some(EphemerisEntry {
+            time: self.epoch + offset,
+            tsince,
+            state: sgp4_with_cache(self.satrec, tsince, Some(&mut self.cache)),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Ephemeris<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::twoline2satrec;
+
+    #[test]
+    fn test_minutes() {
+        let satrec = twoline2satrec(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813",
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656",
+        )
+        .unwrap();
+
+        let entries = Ephemeris::new(&satrec, 0.0, 2880.0, 120.0).collect::<Vec<_>>();
+        assert_eq!(entries.len(), 25);
+
+        let mut legacy = satrec.clone();
+        for (i, entry) in entries.iter().enumerate() {
+            assert_eq!(entry.tsince, i as f64 * 120.0);
+            assert_eq!(entry.time, satrec.epoch() + Duration::minutes(i as i64 * 120));
+            assert_eq!(entry.state, sgp4(&mut legacy, entry.tsince));
+        }
+
+        assert_eq!(Ephemeris::new(&satrec, 0.0, 100.0, 30.0).len(), 4);
+        assert_eq!(Ephemeris::new(&satrec, 0.0, -60.0, -30.0).len(), 3);
+        assert_eq!(Ephemeris::new(&satrec, 0.0, 60.0, -30.0).len(), 0);
+        assert_eq!(Ephemeris::new(&satrec, 0.0, 60.0, 0.0).len(), 0);
+    }
+
+    #[test]
+    fn test_datetimes() {
+        use chrono::TimeZone;
+
+        let satrec = twoline2satrec(
+            "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0    8",
+            "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518  105",
+        )
+        .unwrap();
+
+        // 80275.98708465 is 1980-10-01 23:41:24.113760
+        let epoch = Utc.with_ymd_and_hms(1980, 10, 1, 23, 41, 24).unwrap()
+            + Duration::microseconds(113760);
+        // jdsatepoch, the epoch propagation uses, resolves only tens of microseconds.
+        assert!((satrec.epoch() - epoch).num_microseconds().unwrap().abs() <= 20);
+
+        let start = Utc.with_ymd_and_hms(1980, 10, 2, 0, 0, 0).unwrap();
+        let stop = Utc.with_ymd_and_hms(1980, 10, 2, 1, 0, 0).unwrap();
+        let entries =
+            Ephemeris::from_datetimes(&satrec, start, stop, Duration::minutes(10)).collect::<Vec<_>>();
+
+        assert_eq!(entries.len(), 7);
+        assert_eq!(entries[0].time, start);
+        assert_eq!(entries[6].time, stop);
+
+        for entry in entries {
+            let expected = minutes(entry.time - satrec.epoch());
+            assert!((entry.tsince - expected).abs() < 1e-9);
+            assert_eq!(entry.state, sgp4_with_cache(&satrec, entry.tsince, None));
+
+            // the entry time is the time of the propagated state
+            let state = entry.state.unwrap();
+            let at_time = crate::propogation::propogate_datetime(&satrec, entry.time).unwrap();
+            assert!(state.position.range(&at_time.position) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_errors_per_step() {
+        // decays at 55 minutes; the iterator still yields every step up to the stop time.
+        let satrec = twoline2satrec(
+            "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534",
+            "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708",
+        )
+        .unwrap();
+
+        let entries = Ephemeris::new(&satrec, 0.0, 70.0, 5.0).collect::<Vec<_>>();
+        assert_eq!(entries.len(), 15);
+        assert!(entries[..11].iter().all(|entry| entry.state.is_ok()));
+        assert_eq!(entries[11].state, Err(SGP4Error::DecayCondition));
+    }
+}
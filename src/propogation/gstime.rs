@@ -1,6 +1,6 @@
-use crate::ext;
 use crate::constants::*;
-use chrono::prelude::*;
+use crate::eop::EopTable;
+use crate::time::{Epoch, LeapSecondTable, TimeScale};
 
 /// Convert a julian date to GMST (Greenwich Mean Sidereal Time)
 pub fn gstime(jdut1: f64) -> f64 {
@@ -21,9 +21,27 @@ pub fn gstime(jdut1: f64) -> f64 {
 }
 
 /// Convert a datetime to GMST (Greenwich Mean Sidereal Time)
-pub fn gstime_datetime(datetime: DateTime<Utc>) -> f64 {
-    let jday = ext::jday_datetime(datetime);
-    return gstime(jday)
+///
+/// Accepts a UTC `DateTime` or an `Epoch` on any time scale. It is converted to UT1 with
+/// DUT1 = 0; use [`gstime_epoch`] to supply DUT1.
+pub fn gstime_datetime<T: Into<Epoch>>(datetime: T) -> f64 {
+    gstime_epoch(datetime.into(), LeapSecondTable::embedded(), 0.0)
+}
+
+/// Convert an epoch to GMST (Greenwich Mean Sidereal Time)
+///
+/// `dut1` is UT1 - UTC in seconds.
+pub fn gstime_epoch(epoch: Epoch, leap_seconds: &LeapSecondTable, dut1: f64) -> f64 {
+    let (whole, fraction) = epoch.to_scale_with(TimeScale::Ut1, leap_seconds, dut1).jd_split();
+    gstime(whole + fraction)
+}
+
+/// Convert an epoch to GMST (Greenwich Mean Sidereal Time), taking DUT1 from an EOP table.
+///
+/// DUT1 is taken as zero outside the table.
+pub fn gstime_eop(epoch: Epoch, eop: &EopTable) -> f64 {
+    let dut1 = eop.at(epoch).map_or(0.0, |entry| entry.dut1);
+    gstime_epoch(epoch, LeapSecondTable::embedded(), dut1)
 }
 
 #[cfg(test)]
@@ -34,4 +52,38 @@ mod tests {
         let res = gstime(2444468.79629788);
         assert_eq!(res, 1.265125075734467);
     }
+
+    #[test]
+    fn test_gst_epoch() {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let datetime = Utc.with_ymd_and_hms(2006, 6, 25, 12, 0, 0).unwrap();
+        let epoch = Epoch::from(datetime);
+        assert_eq!(gstime_datetime(datetime), gstime(2453912.0));
+        assert_eq!(gstime_datetime(epoch.to_scale(TimeScale::Tt)), gstime_datetime(datetime));
+
+        // sub-second time is kept, and DUT1 shifts UT1 by the same amount.
+        let later = gstime_datetime(datetime + Duration::milliseconds(500));
+        let dut1 = gstime_epoch(epoch, LeapSecondTable::embedded(), 0.5);
+        crate::tests::assert_diff(later, dut1, 1e-12);
+        crate::tests::assert_diff(later - gstime_datetime(datetime), 0.5 * 7.292115e-5, 1e-8);
+    }
+
+    #[test]
+    fn test_gst_eop() {
+        use crate::eop::EopEntry;
+
+        let eop = EopTable::new(vec![
+            EopEntry { mjd: 53101.0, dut1: -0.4399619, ..EopEntry::default() },
+            EopEntry { mjd: 53102.0, dut1: -0.4414849, ..EopEntry::default() },
+        ])
+        .unwrap();
+
+        let inside = Epoch::from_jd(2453101.5 + 0.25, TimeScale::Utc);
+        let dut1 = eop.at(inside).unwrap().dut1;
+        assert_eq!(gstime_eop(inside, &eop), gstime_epoch(inside, LeapSecondTable::embedded(), dut1));
+
+        let outside = Epoch::from_jd(2453200.5, TimeScale::Utc);
+        assert_eq!(gstime_eop(outside, &eop), gstime_datetime(outside));
+    }
 }
@@ -1,4 +1,5 @@
 use crate::constants::*;
+use crate::io::satnum::CatalogNumber;
 use crate::propogation::dpper::DpperOpsMode;
 use crate::propogation::gstime;
 /*-----------------------------------------------------------------------------
@@ -16,6 +17,7 @@ use crate::propogation::gstime;
 *    inclo       - inclination of satellite
 *    no          - mean motion of satellite
 *    satn        - satellite number
+*    gravity_model - gravity constants to use               wgs72old, wgs72, wgs84
 *
 *  outputs       :
 *    ainv        - 1.0 / a
@@ -54,9 +56,10 @@ use crate::propogation::gstime;
 
 pub struct InitlOptions {
     pub opsmode: DpperOpsMode,
+    pub gravity_model: GravityModel,
     pub method: InitlMethod,
 
-    pub satn: String,
+    pub satn: CatalogNumber,
     pub ecco: f64,
     pub epoch: f64,
     pub inclo: f64,
@@ -94,6 +97,7 @@ pub fn initl(options: InitlOptions) -> InitlReturn {
         epoch,
         inclo,
         opsmode,
+        gravity_model,
         ..
     } = options;
 
@@ -102,6 +106,7 @@ pub fn initl(options: InitlOptions) -> InitlReturn {
     // sgp4fix use old way of finding gst
     // ----------------------- earth constants ---------------------
     // sgp4fix identify constants and allow alternate values
+    let GravityConstants { xke, j2, .. } = gravity_model.constants();
 
     // ------------- calculate auxillary epoch quantities ----------
     let eccsq = ecco * ecco;
@@ -111,8 +116,8 @@ pub fn initl(options: InitlOptions) -> InitlReturn {
     let cosio2 = cosio * cosio;
 
     // ------------------ un-kozai the mean motion -----------------
-    let ak = (XKE / no).powf(X2O3);
-    let d1 = (0.75 * J2 * ((3.0 * cosio2) - 1.0)) / (rteosq * omeosq);
+    let ak = (xke / no).powf(X2O3);
+    let d1 = (0.75 * j2 * ((3.0 * cosio2) - 1.0)) / (rteosq * omeosq);
     let mut del_prime = d1 / (ak * ak);
     let adel = ak
         * (1.0
@@ -121,7 +126,7 @@ pub fn initl(options: InitlOptions) -> InitlReturn {
     del_prime = d1 / (adel * adel);
     no /= 1.0 + del_prime;
 
-    let ao = (XKE / no).powf(X2O3);
+    let ao = (xke / no).powf(X2O3);
     let sinio = inclo.sin();
     let po = ao * omeosq;
     let con42 = 1.0 - (5.0 * cosio2);
@@ -185,13 +190,14 @@ mod test {
     #[test]
     fn test_initl() {
         let opts = InitlOptions {
-            satn: "88888".to_string(),
+            satn: CatalogNumber(88888),
             ecco: 0.0086731,
             epoch: 11232.987084649969,
             inclo: 1.2713589136764896,
             no: 0.07006731262087737,
             method: InitlMethod::N,
             opsmode: DpperOpsMode::I,
+            gravity_model: GravityModel::Wgs84,
         };
 
         let result = initl(opts);
@@ -216,13 +222,14 @@ mod test {
     #[test]
     fn test_initl_ds() {
         let opts = InitlOptions {
-            satn: "11801".to_string(),
+            satn: CatalogNumber(11801),
             ecco: 0.7318036,
             epoch: 11187.29629787989,
             inclo: 0.8166674822761788,
             no: 0.009971844782555844,
             method: InitlMethod::N,
             opsmode: DpperOpsMode::I,
+            gravity_model: GravityModel::Wgs84,
         };
 
         let res = initl(opts);
@@ -242,4 +249,28 @@ mod test {
         assert_similar(res.sinio, 0.7288682597401953);
         assert_eq!(res.gsto, 1.265125075734467);
     }
+
+    #[test]
+    fn test_initl_afspc() {
+        let options = |opsmode| InitlOptions {
+            satn: CatalogNumber(11801),
+            ecco: 0.7318036,
+            epoch: 11187.29629787989,
+            inclo: 0.8166674822761788,
+            no: 0.009971844782555844,
+            method: InitlMethod::N,
+            opsmode,
+            gravity_model: GravityModel::Wgs84,
+        };
+
+        let improved = initl(options(DpperOpsMode::I));
+        let afspc = initl(options(DpperOpsMode::A));
+
+        // Only the sidereal time depends on the operation mode.
+        assert_eq!(afspc.no, improved.no);
+        assert_eq!(afspc.ao, improved.ao);
+        assert_eq!(afspc.gsto, 1.2651250757338985);
+        assert!(afspc.gsto != improved.gsto);
+        assert!((afspc.gsto - improved.gsto).abs() < 1e-9);
+    }
 }
@@ -1,7 +1,10 @@
+pub mod batch;
+pub mod catalog;
 pub mod dpper;
 pub mod dscom;
 pub mod dsinit;
 pub mod dspace;
+pub mod ephemeris;
 pub mod gstime;
 pub mod initl;
 pub mod sgp4;
@@ -10,25 +13,45 @@ pub mod sgp4init;
 
 use crate::constants::*;
 use crate::io::*;
-use crate::ext::*;
 use crate::propogation::sgp4::*;
+use crate::time::{Epoch, TimeScale};
 
-use chrono::prelude::*;
-use chrono::DateTime;
+/// Minutes from the satrec epoch (UTC) to `time`.
+pub(crate) fn minutes_since_epoch(satrec: &Satrec, time: Epoch) -> f64 {
+  let (whole, fraction) = time.to_scale(TimeScale::Utc).jd_split();
+  ((whole - satrec.jdsatepoch) + fraction) * MINUTES_PER_DAY
+}
 
 /// Propogate changes to a satrec for a specific datetime.
-pub fn propogate_datetime(satrec: &Satrec, time: DateTime<Utc>) -> Result<SGP4Result, SGP4Error> {
-  let j = jday(
-    time.year() as f64,
-    time.month() as f64,
-    time.day() as f64,
-    time.hour() as f64,
-    time.minute() as f64,
-    time.second() as f64,
-    0 as f64
-  );
-
-  let m = (j - satrec.jdsatepoch) * MINUTES_PER_DAY;
-  let mut rec = satrec.clone();
-  return sgp4(&mut rec, m);
+///
+/// Accepts a UTC `DateTime` or an `Epoch` on any time scale.
+pub fn propogate_datetime<T: Into<Epoch>>(satrec: &Satrec, time: T) -> Result<SGP4Result, SGP4Error> {
+  let m = minutes_since_epoch(satrec, time.into());
+  return sgp4_with_cache(satrec, m, None);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{Duration, TimeZone, Utc};
+
+  #[test]
+  fn test_propogate_epoch() {
+    let satrec = twoline2satrec(
+      "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0    8",
+      "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518  105",
+    ).unwrap();
+
+    let datetime = Utc.with_ymd_and_hms(1980, 10, 2, 0, 0, 0).unwrap() + Duration::microseconds(250);
+    let tai = Epoch::from(datetime).to_scale(TimeScale::Tai);
+    assert_eq!(tai.to_datetime(), Some(datetime + Duration::seconds(19)));
+
+    let expected = sgp4_with_cache(&satrec, minutes_since_epoch(&satrec, datetime.into()), None);
+    assert_eq!(propogate_datetime(&satrec, datetime), expected);
+    assert_eq!(propogate_datetime(&satrec, tai), expected);
+
+    // the 250 microseconds are not dropped.
+    let whole = propogate_datetime(&satrec, datetime - Duration::microseconds(250)).unwrap();
+    assert!(whole.position.range(&expected.unwrap().position) > 1e-3);
+  }
 }
@@ -1,7 +1,9 @@
+use crate::anomaly::kepler_equinoctial;
 use crate::constants::*;
 use crate::io::Satrec;
 use crate::propogation::dpper::*;
 use crate::propogation::initl::InitlMethod;
+use crate::frames::{FrameVector, State, Teme};
 use crate::{Vec3, Eci};
 /*----------------------------------------------------------------------------
 *
@@ -96,6 +98,16 @@ pub struct SGP4Result {
     pub velocity: Vec3,
 }
 
+impl SGP4Result {
+    /// Position and velocity, tagged as being in the TEME frame.
+    pub fn teme(&self) -> State<Teme> {
+        State {
+            position: FrameVector::from_vec3(&self.position),
+            velocity: FrameVector::from_vec3(&self.velocity),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum SGP4Error {
     Eccentricty,
@@ -117,34 +129,77 @@ impl SGP4Error {
     }
 }
 
+/// Deep-space resonance integrator state carried between propagation calls.
+///
+/// For 12 and 24 hour resonant orbits the integrator steps from the epoch to the
+/// requested time. Keeping its state lets successive calls continue from the last
+/// time instead of starting over; a default cache starts from the epoch on first use.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct IntegratorCache {
+    /// Time of the last integration step (minutes since epoch).
+    pub atime: f64,
+    pub xli: f64,
+    pub xni: f64,
+}
+
+/// Propogate a satrec to `tsince` minutes after epoch.
+///
+/// The satrec keeps the deep-space integrator state and the error code of the last
+/// call. Use [`sgp4_with_cache`] to propogate a shared satrec without mutating it.
 pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
-    let mut coseo1 = 0.0;
-    let mut sineo1 = 0.0;
+    let mut cache = IntegratorCache {
+        atime: satrec.atime,
+        xli: satrec.xli,
+        xni: satrec.xni,
+    };
+
+    let result = sgp4_with_cache(satrec, tsince, Some(&mut cache));
+
+    satrec.t = tsince;
+    satrec.atime = cache.atime;
+    satrec.xli = cache.xli;
+    satrec.xni = cache.xni;
+    satrec.error = match &result {
+        Ok(_) => 0,
+        Err(err) => u32::from(err.code()),
+    };
+
+    result
+}
+
+/// Propogate a satrec to `tsince` minutes after epoch without mutating it.
+///
+/// Deep-space resonance integration continues from `cache` and stores its new state
+/// there. Without a cache the integration starts over from the epoch, which is slower
+/// for long spans but independent of previous calls.
+pub fn sgp4_with_cache(
+    satrec: &Satrec,
+    tsince: f64,
+    cache: Option<&mut IntegratorCache>,
+) -> Result<SGP4Result, SGP4Error> {
     let mut cosip;
     let mut sinip;
     let cosisq;
     let delm;
     let delomg;
-    let mut eo1;
     let mut argpm;
-    let argpp;
+    let mut argpp;
     let mut su;
     let t3;
     let t4;
     let tc;
-    let mut tem5: f64;
     let mut temp;
     let mut tempa;
     let mut tempe;
     let mut templ;
-    let inclm;
+    let mut inclm;
     let mut mm;
     let mut nm;
     let mut nodem;
     let mut xincp;
     let mut xlm;
-    let mp;
-    let nodep;
+    let mut mp;
+    let mut nodep;
 
     /* ------------------ set mathematical constants --------------- */
     // sgp4fix divisor for divide by zero check on inclination
@@ -153,43 +208,57 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
 
     const TEMP4: f64 = 1.5e-12;
 
-    let vkmpersec: f64 = (EARTH_RADIUS * XKE) / 60.0;
+    let GravityConstants {
+        earth_radius,
+        xke,
+        j2,
+        j3oj2,
+        ..
+    } = satrec.gravity_model.constants();
 
-    // --------------------- clear sgp4 error flag -----------------
-    satrec.t = tsince;
-    satrec.error = 0;
+    let vkmpersec: f64 = (earth_radius * xke) / 60.0;
+
+    // the deep-space path recomputes these from the perturbed inclination.
+    let mut aycof = satrec.aycof;
+    let mut xlcof = satrec.xlcof;
+    let mut con41 = satrec.con41;
+    let mut x1mth2 = satrec.x1mth2;
+    let mut x7thm1 = satrec.x7thm1;
+
+    let mut fresh = IntegratorCache::default();
+    let cache = cache.unwrap_or(&mut fresh);
 
     //  ------- update for secular gravity and atmospheric drag -----
-    let xmdf = satrec.mo + (satrec.mdot * satrec.t);
-    let argpdf = satrec.argpo + (satrec.argpdot * satrec.t);
-    let nodedf = satrec.nodeo + (satrec.nodedot * satrec.t);
+    let xmdf = satrec.mo + (satrec.mdot * tsince);
+    let argpdf = satrec.argpo + (satrec.argpdot * tsince);
+    let nodedf = satrec.nodeo + (satrec.nodedot * tsince);
     argpm = argpdf;
     mm = xmdf;
-    let t2 = satrec.t * satrec.t;
+    let t2 = tsince * tsince;
     nodem = nodedf + (satrec.nodecf * t2);
-    tempa = 1.0 - (satrec.cc1 * satrec.t);
-    tempe = satrec.bstar * satrec.cc4 * satrec.t;
+    tempa = 1.0 - (satrec.cc1 * tsince);
+    tempe = satrec.bstar * satrec.cc4 * tsince;
     templ = satrec.t2cof * t2;
 
     if satrec.isimp != 1 {
-        delomg = satrec.omgcof * satrec.t;
+        delomg = satrec.omgcof * tsince;
         //  sgp4fix use mutliply for speed instead of pow
         let delmtemp = 1.0 + (satrec.eta * xmdf.cos());
         delm = satrec.xmcof * ((delmtemp * delmtemp * delmtemp) - satrec.delmo);
         temp = delomg + delm;
         mm = xmdf + temp;
         argpm = argpdf - temp;
-        t3 = t2 * satrec.t;
-        t4 = t3 * satrec.t;
+        t3 = t2 * tsince;
+        t4 = t3 * tsince;
         tempa = tempa - (satrec.d2 * t2) - (satrec.d3 * t3) - (satrec.d4 * t4);
         tempe += satrec.bstar * satrec.cc5 * (mm.sin() - satrec.sinmao);
-        templ = templ + (satrec.t3cof * t3) + (t4 * (satrec.t4cof + (satrec.t * satrec.t5cof)));
+        templ = templ + (satrec.t3cof * t3) + (t4 * (satrec.t4cof + (tsince * satrec.t5cof)));
     }
     nm = satrec.no;
     let mut em = satrec.ecco;
     inclm = satrec.inclo;
     if satrec.method == InitlMethod::D {
-        tc = satrec.t;
+        tc = tsince;
 
         let dspace_options = crate::propogation::dspace::DspaceOptions {
             irez: satrec.irez,
@@ -213,47 +282,47 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
             domdt: satrec.domdt,
             argpo: satrec.argpo,
             argpdot: satrec.argpdot,
-            t: satrec.t,
+            t: tsince,
             tc,
             gsto: satrec.gsto,
             xfact: satrec.xfact,
             xlamo: satrec.xlamo,
             no: satrec.no,
-            atime: satrec.atime,
+            atime: cache.atime,
             em,
             argpm,
             inclm,
-            xli: satrec.xli,
+            xli: cache.xli,
             mm,
-            xni: satrec.xni,
+            xni: cache.xni,
             nodem,
             nm,
         };
 
         let dspace_result = crate::propogation::dspace::dspace(dspace_options);
 
-        let crate::propogation::dspace::DspaceResult {
-            argpm: _,
-            inclm: _,
-            mm: _,
-            nodem: _,
-            nm: _,
-            ..
-        } = dspace_result;
+        cache.atime = dspace_result.atime;
+        cache.xli = dspace_result.xli;
+        cache.xni = dspace_result.xni;
+
         em = dspace_result.em;
+        argpm = dspace_result.argpm;
+        inclm = dspace_result.inclm;
+        mm = dspace_result.mm;
+        nodem = dspace_result.nodem;
+        nm = dspace_result.nm;
     }
 
     if nm <= 0.0 {
         // printf("// error nm %f\n", nm);
-        satrec.error = 2;
         // sgp4fix add return
         // return [false, false];
         // panic!("error 2")
         return Err(SGP4Error::Nm);
     }
 
-    let am = ((XKE / nm).powf(X2O3)) * tempa * tempa;
-    nm = XKE / (am.powf(1.5));
+    let am = ((xke / nm).powf(X2O3)) * tempa * tempa;
+    nm = xke / (am.powf(1.5));
     em -= tempe;
 
     // fix tolerance for error recognition
@@ -261,7 +330,6 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
     if em >= 1.0 || em < -0.001 {
         // || (am < 0.95)
         // printf("// error em %f\n", em);
-        satrec.error = 1;
         // sgp4fix to return if there is an error in eccentricity
         // return [false, false];
         // panic!("error 1");
@@ -285,7 +353,7 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
     let cosim = inclm.cos();
 
     // -------------------- add lunar-solar periodics --------------
-    let ep = em;
+    let mut ep = em;
     xincp = inclm;
     argpp = argpm;
     nodep = nodem;
@@ -294,6 +362,7 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
     cosip = cosim;
     if satrec.method == InitlMethod::D {
         let dpper_parameters = DpperOptions {
+            t: tsince,
             inclo: satrec.inclo,
             init: DpperInit::N,
             ep,
@@ -301,19 +370,16 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
             nodep,
             argpp,
             mp,
-            opsmode: satrec.operationmode.clone(),
+            opsmode: satrec.operationmode,
         };
 
-        let dpper_result = dpper(&satrec, dpper_parameters);
-        let DpperResult {
-            ep,
-            mut nodep,
-            mut argpp,
-            mp: _,
-            ..
-        } = dpper_result;
+        let dpper_result = dpper(satrec, dpper_parameters);
 
+        ep = dpper_result.ep;
         xincp = dpper_result.inclp;
+        nodep = dpper_result.nodep;
+        argpp = dpper_result.argpp;
+        mp = dpper_result.mp;
 
         if xincp < 0.0 {
             xincp = -xincp;
@@ -322,7 +388,6 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
         }
         if ep < 0.0 || ep > 1.0 {
             //  printf("// error ep %f\n", ep);
-            satrec.error = 3;
             //  sgp4fix add return
             // return [false, false];
             // panic!("error 3");
@@ -334,44 +399,24 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
     if satrec.method == InitlMethod::D {
         sinip = xincp.sin();
         cosip = xincp.cos();
-        satrec.aycof = -0.5 * J3OJ2 * sinip;
+        aycof = -0.5 * j3oj2 * sinip;
 
         //  sgp4fix for divide by zero for xincp = 180 deg
         if (cosip + 1.0).abs() > 1.5e-12 {
-            satrec.xlcof = (-0.25 * J3OJ2 * sinip * (3.0 + (5.0 * cosip))) / (1.0 + cosip);
+            xlcof = (-0.25 * j3oj2 * sinip * (3.0 + (5.0 * cosip))) / (1.0 + cosip);
         } else {
-            satrec.xlcof = (-0.25 * J3OJ2 * sinip * (3.0 + (5.0 * cosip))) / TEMP4;
+            xlcof = (-0.25 * j3oj2 * sinip * (3.0 + (5.0 * cosip))) / TEMP4;
         }
     }
 
     let axnl = ep * argpp.cos();
     temp = 1.0 / (am * (1.0 - (ep * ep)));
-    let aynl = (ep * argpp.sin()) + (temp * satrec.aycof);
-    let xl = mp + argpp + nodep + (temp * satrec.xlcof * axnl);
+    let aynl = (ep * argpp.sin()) + (temp * aycof);
+    let xl = mp + argpp + nodep + (temp * xlcof * axnl);
 
     // --------------------- solve kepler's equation ---------------
     let u = (xl - nodep) % TWO_PI;
-    eo1 = u;
-    tem5 = 9999.9;
-    let mut ktr = 1;
-
-    //    sgp4fix for kepler iteration
-    //    the following iteration needs better limits on corrections
-    while tem5.abs() >= 1.0e-12 && ktr <= 10 {
-        sineo1 = eo1.sin();
-        coseo1 = eo1.cos();
-        tem5 = 1.0 - (coseo1 * axnl) - (sineo1 * aynl);
-        tem5 = (((u - (aynl * coseo1)) + (axnl * sineo1)) - eo1) / tem5;
-        if tem5.abs() >= 0.95 {
-            if tem5 > 0.0 {
-                tem5 = 0.95;
-            } else {
-                tem5 = -0.95;
-            }
-        }
-        eo1 += tem5;
-        ktr += 1;
-    }
+    let (sineo1, coseo1) = kepler_equinoctial(u, axnl, aynl);
 
     //  ------------- short period preliminary quantities -----------
     let ecose = (axnl * coseo1) + (aynl * sineo1);
@@ -380,7 +425,6 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
     let pl = am * (1.0 - el2);
     if pl < 0.0 {
         //  printf("// error pl %f\n", pl);
-        satrec.error = 4;
         //  sgp4fix add return
         // return [false, false];
         // panic!("Error 4: pl: {}", pl);
@@ -398,24 +442,24 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
     let sin2u = (cosu + cosu) * sinu;
     let cos2u = 1.0 - (2.0 * sinu * sinu);
     temp = 1.0 / pl;
-    let temp1 = 0.5 * J2 * temp;
+    let temp1 = 0.5 * j2 * temp;
     let temp2 = temp1 * temp;
 
     // -------------- update for short period periodics ------------
     if satrec.method == InitlMethod::D {
         cosisq = cosip * cosip;
-        satrec.con41 = (3.0 * cosisq) - 1.0;
-        satrec.x1mth2 = 1.0 - cosisq;
-        satrec.x7thm1 = (7.0 * cosisq) - 1.0;
+        con41 = (3.0 * cosisq) - 1.0;
+        x1mth2 = 1.0 - cosisq;
+        x7thm1 = (7.0 * cosisq) - 1.0;
     }
 
     let mrt =
-        (rl * (1.0 - (1.5 * temp2 * betal * satrec.con41))) + (0.5 * temp1 * satrec.x1mth2 * cos2u);
-    su -= 0.25 * temp2 * satrec.x7thm1 * sin2u;
+        (rl * (1.0 - (1.5 * temp2 * betal * con41))) + (0.5 * temp1 * x1mth2 * cos2u);
+    su -= 0.25 * temp2 * x7thm1 * sin2u;
     let xnode = nodep + (1.5 * temp2 * cosip * sin2u);
     let xinc = xincp + (1.5 * temp2 * cosip * sinip * cos2u);
-    let mvt = rdotl - ((nm * temp1 * satrec.x1mth2 * sin2u) / XKE);
-    let rvdot = rvdotl + ((nm * temp1 * ((satrec.x1mth2 * cos2u) + (1.5 * satrec.con41))) / XKE);
+    let mvt = rdotl - ((nm * temp1 * x1mth2 * sin2u) / xke);
+    let rvdot = rvdotl + ((nm * temp1 * ((x1mth2 * cos2u) + (1.5 * con41))) / xke);
 
     // --------------------- orientation vectors -------------------
     let sinsu = su.sin();
@@ -435,9 +479,9 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
 
     // --------- position and velocity (in km and km/sec) ----------
     let r = Eci {
-        x: (mrt * ux) * EARTH_RADIUS,
-        y: (mrt * uy) * EARTH_RADIUS,
-        z: (mrt * uz) * EARTH_RADIUS,
+        x: (mrt * ux) * earth_radius,
+        y: (mrt * uy) * earth_radius,
+        z: (mrt * uz) * earth_radius,
     };
     let v = Vec3 {
         x: ((mvt * ux) + (rvdot * vx)) * vkmpersec,
@@ -448,7 +492,6 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
     // sgp4fix for decaying satellites
     if mrt < 1.0 {
         // printf("// decay condition %11.6f \n",mrt);
-        satrec.error = 6;
         // return {
         //   position: false,
         //   velocity: false,
@@ -464,3 +507,75 @@ pub fn sgp4(satrec: &mut Satrec, tsince: f64) -> Result<SGP4Result, SGP4Error> {
         velocity: v,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::twoline2satrec;
+
+    // 12 hour resonant Molniya orbit from the verification catalog.
+    const TLE1: &str = "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813";
+    const TLE2: &str = "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656";
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Satrec>();
+        assert_send_sync::<IntegratorCache>();
+    }
+
+    #[test]
+    fn test_with_cache() {
+        let satrec = twoline2satrec(TLE1, TLE2).unwrap();
+        let before = (satrec.t, satrec.atime, satrec.xli, satrec.xni);
+
+        let mut legacy = satrec.clone();
+        let mut cache = IntegratorCache::default();
+
+        for step in 0..=24 {
+            let t = step as f64 * 120.0;
+            let expected = sgp4(&mut legacy, t).unwrap();
+
+            assert_eq!(sgp4_with_cache(&satrec, t, Some(&mut cache)).unwrap(), expected);
+            assert_eq!(cache.atime, legacy.atime);
+            assert_eq!(cache.xni, legacy.xni);
+
+            let uncached = sgp4_with_cache(&satrec, t, None).unwrap();
+            assert!(uncached.position.range(&expected.position) < 1e-6);
+        }
+
+        assert_eq!((satrec.t, satrec.atime, satrec.xli, satrec.xni), before);
+    }
+
+    #[test]
+    fn test_threads() {
+        let satrec = twoline2satrec(TLE1, TLE2).unwrap();
+        let expected = sgp4_with_cache(&satrec, 1440.0, None).unwrap();
+
+        std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| scope.spawn(|| sgp4_with_cache(&satrec, 1440.0, None).unwrap()))
+                .collect::<Vec<_>>();
+
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn test_error_flag() {
+        // decays within the hour.
+        let mut satrec = twoline2satrec(
+            "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534",
+            "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708",
+        )
+        .unwrap();
+
+        assert_eq!(sgp4_with_cache(&satrec, 60.0, None), Err(SGP4Error::DecayCondition));
+        assert_eq!(satrec.error, 0);
+
+        assert_eq!(sgp4(&mut satrec, 60.0), Err(SGP4Error::DecayCondition));
+        assert_eq!(satrec.error, 6);
+    }
+}
@@ -11,6 +11,7 @@ use crate::constants::*;
 *
 *  inputs        :
 *    opsmode     - mode of operation afspc or improved 'a', 'i'
+*    gravity_model - which set of constants to use  wgs72old, wgs72, wgs84
 *    satn        - satellite number
 *    bstar       - sgp4 type drag coefficient              kg/m2er
 *    ecco        - eccentricity
@@ -82,6 +83,7 @@ use crate::constants::*;
 *    vallado, crawford, hujsak, kelso  2006
 ----------------------------------------------------------------------------*/
 
+use crate::io::satnum::CatalogNumber;
 use crate::io::Satrec;
 use crate::propogation::dpper::*;
 use crate::propogation::dscom::*;
@@ -91,7 +93,8 @@ use crate::propogation::sgp4::*;
 
 pub struct SGP4InitOptions {
     pub opsmode: DpperOpsMode,
-    pub satn: String,
+    pub gravity_model: GravityModel,
+    pub satn: CatalogNumber,
     pub epoch: f64,
     pub xbstar: f64,
     pub xecco: f64,
@@ -104,6 +107,7 @@ pub struct SGP4InitOptions {
 pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Result, SGP4Error> {
     let SGP4InitOptions {
         opsmode,
+        gravity_model,
         satn,
         epoch,
         xbstar,
@@ -276,17 +280,33 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
     satrec.inclo = xinclo;
     satrec.mo = xmo;
     satrec.no = xno;
+    satrec.no_kozai = xno;
     satrec.nodeo = xnodeo;
 
     //  sgp4fix add opsmode
     satrec.operationmode = opsmode;
+    satrec.gravity_model = gravity_model;
 
     // ------------------------ earth constants -----------------------
     // sgp4fix identify constants and allow alternate values
+    let GravityConstants {
+        tumin,
+        earth_radius,
+        xke,
+        j2,
+        j3oj2,
+        j4,
+        ..
+    } = gravity_model.constants();
+
+    // the parsers fill these in with the default constants, refresh them for this model
+    satrec.a = (satrec.no * tumin).powf(-2.0 / 3.0);
+    satrec.alta = (satrec.a * (1.0 + satrec.ecco)) - 1.0;
+    satrec.altp = (satrec.a * (1.0 - satrec.ecco)) - 1.0;
 
-    let ss = (78.0 / EARTH_RADIUS) + 1.0;
+    let ss = (78.0 / earth_radius) + 1.0;
     // sgp4fix use multiply for speed instead of pow
-    let qzms2ttemp = (120.0 - 78.0) / EARTH_RADIUS;
+    let qzms2ttemp = (120.0 - 78.0) / earth_radius;
     let qzms2t = qzms2ttemp * qzms2ttemp * qzms2ttemp * qzms2ttemp;
 
     satrec.init = DpperInit::Y;
@@ -301,7 +321,8 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
         no: satrec.no,
 
         method: satrec.method.clone(),
-        opsmode: satrec.operationmode.clone(),
+        opsmode: satrec.operationmode,
+        gravity_model,
     };
 
     let initl_result = initl(initl_options);
@@ -337,12 +358,12 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
     if omeosq >= 0.0 || satrec.no >= 0.0 {
         satrec.isimp = 0;
         // TODO: logic mode error.
-        if rp < (220.0 / EARTH_RADIUS) + 1.0 {
+        if rp < (220.0 / earth_radius) + 1.0 {
             satrec.isimp = 1;
         }
         sfour = ss;
         qzms24 = qzms2t;
-        perige = (rp - 1.0) * EARTH_RADIUS;
+        perige = (rp - 1.0) * earth_radius;
 
         // - for perigees below 156 km, s and qoms2t are altered -
         if perige < 156.0 {
@@ -352,9 +373,9 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
             }
 
             // sgp4fix use multiply for speed instead of pow
-            let qzms24temp = (120.0 - sfour) / EARTH_RADIUS;
+            let qzms24temp = (120.0 - sfour) / earth_radius;
             qzms24 = qzms24temp * qzms24temp * qzms24temp * qzms24temp;
-            sfour = (sfour / EARTH_RADIUS) + 1.0;
+            sfour = (sfour / earth_radius) + 1.0;
         }
         pinvsq = 1.0 / posq;
 
@@ -368,13 +389,13 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
         cc2 = coef1
             * satrec.no
             * ((ao * (1.0 + (1.5 * etasq) + (eeta * (4.0 + etasq))))
-                + (((0.375 * J2 * tsi) / psisq)
+                + (((0.375 * j2 * tsi) / psisq)
                     * satrec.con41
                     * (8.0 + (3.0 * etasq * (8.0 + etasq)))));
         satrec.cc1 = satrec.bstar * cc2;
         cc3 = 0.0;
         if satrec.ecco > 1.0e-4 {
-            cc3 = (-2.0 * coef * tsi * J3OJ2 * satrec.no * sinio) / satrec.ecco;
+            cc3 = (-2.0 * coef * tsi * j3oj2 * satrec.no * sinio) / satrec.ecco;
         }
         satrec.x1mth2 = 1.0 - cosio2;
         satrec.cc4 = 2.0
@@ -383,7 +404,7 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
             * ao
             * omeosq
             * (((satrec.eta * (2.0 + (0.5 * etasq))) + (satrec.ecco * (0.5 + (2.0 * etasq))))
-                - (((J2 * tsi) / (ao * psisq))
+                - (((j2 * tsi) / (ao * psisq))
                     * ((-3.0
                         * satrec.con41
                         * ((1.0 - (2.0 * eeta)) + (etasq * (1.5 - (0.5 * eeta)))))
@@ -393,9 +414,9 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
                             * (2.0 * satrec.argpo).cos()))));
         satrec.cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + (2.75 * (etasq + eeta)) + (eeta * etasq));
         cosio4 = cosio2 * cosio2;
-        temp1 = 1.5 * J2 * pinvsq * satrec.no;
-        temp2 = 0.5 * temp1 * J2 * pinvsq;
-        temp3 = -0.46875 * J4 * pinvsq * pinvsq * satrec.no;
+        temp1 = 1.5 * j2 * pinvsq * satrec.no;
+        temp2 = 0.5 * temp1 * j2 * pinvsq;
+        temp3 = -0.46875 * j4 * pinvsq * pinvsq * satrec.no;
         satrec.mdot = satrec.no
             + (0.5 * temp1 * rteosq * satrec.con41)
             + (0.0625 * temp2 * rteosq * ((13.0 - (78.0 * cosio2)) + (137.0 * cosio4)));
@@ -417,11 +438,11 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
 
         // sgp4fix for divide by zero with xinco = 180 deg
         if (cosio + 1.0).abs() > 1.5e-12 {
-            satrec.xlcof = (-0.25 * J3OJ2 * sinio * (3.0 + (5.0 * cosio))) / (1.0 + cosio);
+            satrec.xlcof = (-0.25 * j3oj2 * sinio * (3.0 + (5.0 * cosio))) / (1.0 + cosio);
         } else {
-            satrec.xlcof = (-0.25 * J3OJ2 * sinio * (3.0 + (5.0 * cosio))) / TEMP4;
+            satrec.xlcof = (-0.25 * j3oj2 * sinio * (3.0 + (5.0 * cosio))) / TEMP4;
         }
-        satrec.aycof = -0.5 * J3OJ2 * sinio;
+        satrec.aycof = -0.5 * j3oj2 * sinio;
 
         // sgp4fix use multiply for speed instead of pow
         let delmotemp = 1.0 + (satrec.eta * (satrec.mo).cos());
@@ -566,6 +587,7 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
             } = dscom_result;
 
             let dpper_options = DpperOptions {
+                t: satrec.t,
                 inclo: inclm,
                 init: satrec.init.clone(),
                 ep: satrec.ecco,
@@ -573,7 +595,7 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
                 nodep: satrec.nodeo,
                 argpp: satrec.argpo,
                 mp: satrec.mo,
-                opsmode: satrec.operationmode.clone(),
+                opsmode: satrec.operationmode,
             };
 
             let dpper_result = dpper(&satrec, dpper_options);
@@ -589,6 +611,7 @@ pub fn sgp4init(satrec: &mut Satrec, options: SGP4InitOptions) -> Result<SGP4Res
             mm = 0.0;
 
             let dsinit_options = DsinitOptions {
+                xke,
                 cosim,
                 emsq,
                 argpo: satrec.argpo,
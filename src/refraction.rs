@@ -0,0 +1,175 @@
+//! Atmospheric refraction of look angles.
+//!
+//! Look angles from [`ecf_to_look_angles`](crate::transforms::ecf_to_look_angles) are
+//! geometric. The atmosphere bends the line of sight so a satellite appears higher, by
+//! about 0.5° at the horizon and a few arcminutes at 10°. Saemundsson's formula gives the
+//! apparent elevation from the geometric one and Bennett's the reverse; the two are
+//! inverses of each other to within 0.1 arcminute above the horizon. Both are scaled for
+//! pressure and temperature as in Meeus, Astronomical Algorithms, ch. 16.
+
+use crate::constants::*;
+use crate::Bearing;
+
+/// Below this elevation, in degrees, the refraction is held at its value there.
+const LOWEST_ELEVATION: f64 = -1.0;
+
+/// Refraction model for an observer's atmospheric conditions.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Refraction {
+    /// Pressure at the observer, in millibars (hPa).
+    pub pressure: f64,
+
+    /// Temperature at the observer, in °C.
+    pub temperature: f64,
+}
+
+impl Default for Refraction {
+    fn default() -> Refraction {
+        Refraction::STANDARD
+    }
+}
+
+impl Refraction {
+    /// The conditions of Bennett's and Saemundsson's formulas: 1010 mbar and 10 °C.
+    pub const STANDARD: Refraction = Refraction {
+        pressure: 1010.0,
+        temperature: 10.0,
+    };
+
+    pub fn new(pressure: f64, temperature: f64) -> Refraction {
+        Refraction {
+            pressure,
+            temperature,
+        }
+    }
+
+    /// Scale of the refraction relative to standard conditions.
+    fn scale(&self) -> f64 {
+        (self.pressure / 1010.0) * (283.0 / (273.0 + self.temperature))
+    }
+
+    /// Apparent elevation from a geometric elevation, in radians (Saemundsson).
+    pub fn apparent_elevation(&self, geometric: f64) -> f64 {
+        let h = (geometric * RAD_TO_DEG).max(LOWEST_ELEVATION);
+        let arcminutes = 1.02 / ((h + 10.3 / (h + 5.11)) * DEG_2_RAD).tan();
+
+        geometric + self.scale() * arcminutes.max(0.0) / 60.0 * DEG_2_RAD
+    }
+
+    /// Geometric elevation from an apparent elevation, in radians (Bennett).
+    ///
+    /// Use this to plan from apparent quantities, such as a horizon surveyed by eye or an
+    /// optical pointing.
+    pub fn geometric_elevation(&self, apparent: f64) -> f64 {
+        let h = (apparent * RAD_TO_DEG).max(LOWEST_ELEVATION);
+        let arcminutes = 1.0 / ((h + 7.31 / (h + 4.4)) * DEG_2_RAD).tan();
+
+        apparent - self.scale() * arcminutes.max(0.0) / 60.0 * DEG_2_RAD
+    }
+
+    /// Geometric look angles with the elevation refracted to where the satellite appears.
+    pub fn apply(&self, look_angles: &Bearing) -> Bearing {
+        Bearing {
+            azimuth: look_angles.azimuth,
+            elevation: self.apparent_elevation(look_angles.elevation),
+            range: look_angles.range,
+        }
+    }
+
+    /// Apparent look angles with the refraction removed from the elevation.
+    pub fn remove(&self, look_angles: &Bearing) -> Bearing {
+        Bearing {
+            azimuth: look_angles.azimuth,
+            elevation: self.geometric_elevation(look_angles.elevation),
+            range: look_angles.range,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+
+    const ARCMINUTE: f64 = DEG_2_RAD / 60.0;
+
+    #[test]
+    fn test_standard() {
+        let standard = Refraction::default();
+
+        // Bennett: 34.5' for an object seen on the horizon; Saemundsson: 29.0' for one
+        // geometrically on it.
+        assert_diff(
+            standard.geometric_elevation(0.0),
+            -34.4775 * ARCMINUTE,
+            1e-3 * ARCMINUTE,
+        );
+        assert_diff(
+            standard.apparent_elevation(0.0),
+            28.9819 * ARCMINUTE,
+            1e-3 * ARCMINUTE,
+        );
+        assert_diff(
+            standard.apparent_elevation(10.0 * DEG_2_RAD) - 10.0 * DEG_2_RAD,
+            5.4077 * ARCMINUTE,
+            1e-3 * ARCMINUTE,
+        );
+
+        // none at the zenith
+        assert_eq!(standard.apparent_elevation(PI / 2.0), PI / 2.0);
+
+        // the formulas are inverses to 0.1' above the horizon
+        for i in 0..=90 {
+            let geometric = f64::from(i) * DEG_2_RAD;
+            let apparent = standard.apparent_elevation(geometric);
+            assert!(apparent >= geometric);
+            assert_diff(
+                standard.geometric_elevation(apparent),
+                geometric,
+                0.1 * ARCMINUTE,
+            );
+        }
+
+        // monotonic, including below the horizon
+        let mut previous = standard.apparent_elevation(-0.5);
+        for i in -499..=500 {
+            let apparent = standard.apparent_elevation(f64::from(i) * 1e-3);
+            assert!(apparent > previous);
+            previous = apparent;
+        }
+    }
+
+    #[test]
+    fn test_conditions() {
+        let standard = Refraction::STANDARD;
+        let cold_high = Refraction::new(505.0, -10.0);
+        let geometric = 2.0 * DEG_2_RAD;
+
+        let bend = |refraction: Refraction| refraction.apparent_elevation(geometric) - geometric;
+        assert_diff(bend(cold_high), bend(standard) * 0.5 * 283.0 / 263.0, 1e-15);
+        assert_eq!(
+            Refraction::new(0.0, 10.0).apparent_elevation(geometric),
+            geometric
+        );
+    }
+
+    #[test]
+    fn test_look_angles() {
+        let geometric = Bearing {
+            azimuth: 1.0,
+            elevation: 0.05,
+            range: 2000.0,
+        };
+
+        let refraction = Refraction::new(980.0, 25.0);
+        let apparent = refraction.apply(&geometric);
+        assert_eq!(apparent.azimuth, geometric.azimuth);
+        assert_eq!(apparent.range, geometric.range);
+        assert_eq!(apparent.elevation, refraction.apparent_elevation(0.05));
+        assert_diff(
+            refraction.remove(&apparent).elevation,
+            0.05,
+            0.1 * ARCMINUTE,
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! Time scales, and epochs that keep track of which scale they are in.
+//!
+//! Conversions go through TAI. UTC differs from TAI by a whole number of leap seconds,
+//! taken from a [`LeapSecondTable`]; UT1 differs from UTC by DUT1, which has to be
+//! supplied (it is zero unless given, which is good to within 0.9 s).
+
+use chrono::{DateTime, TimeZone, Utc};
+use std::sync::OnceLock;
+
+/// Seconds per day.
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Nanoseconds per second.
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+/// Unix timestamp of the J2000 epoch, 2000-01-01 12:00:00.
+const J2000_UNIX: i64 = 946_728_000;
+
+/// Julian date of the J2000 epoch.
+const J2000_JD: f64 = 2_451_545.0;
+
+/// Modified julian date of the J2000 epoch.
+const J2000_MJD: f64 = 51_544.5;
+
+/// Modified julian date of the NTP epoch, 1900-01-01 00:00:00.
+const NTP_MJD: i64 = 15_020;
+
+/// TT - TAI, in nanoseconds.
+const TT_MINUS_TAI: i64 = 32_184_000_000;
+
+/// TAI - GPS, in nanoseconds.
+const TAI_MINUS_GPS: i64 = 19_000_000_000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TimeScale {
+    /// Coordinated Universal Time.
+    Utc,
+    /// International Atomic Time.
+    Tai,
+    /// Terrestrial Time, TAI + 32.184 s.
+    Tt,
+    /// Universal Time, following the rotation of the earth. UTC + DUT1.
+    Ut1,
+    /// GPS time, TAI - 19 s.
+    Gps,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LeapSecondError {
+    /// A data line could not be read (line number, line).
+    InvalidLine(usize, String),
+    /// The file has no leap second entries.
+    Empty,
+    /// The file could not be read.
+    Io(String),
+}
+
+/// TAI - UTC in whole seconds, from the dates each value took effect.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LeapSecondTable {
+    /// UTC seconds since J2000 at which each offset starts, in increasing order.
+    entries: Vec<(i64, i64)>,
+}
+
+/// Leap seconds announced up to IERS Bulletin C 70 (no leap second before 2026).
+const LEAP_SECONDS: [(i32, u32, i64); 28] = [
+    (1972, 1, 10),
+    (1972, 7, 11),
+    (1973, 1, 12),
+    (1974, 1, 13),
+    (1975, 1, 14),
+    (1976, 1, 15),
+    (1977, 1, 16),
+    (1978, 1, 17),
+    (1979, 1, 18),
+    (1980, 1, 19),
+    (1981, 7, 20),
+    (1982, 7, 21),
+    (1983, 7, 22),
+    (1985, 7, 23),
+    (1988, 1, 24),
+    (1990, 1, 25),
+    (1991, 1, 26),
+    (1992, 7, 27),
+    (1993, 7, 28),
+    (1994, 7, 29),
+    (1996, 1, 30),
+    (1997, 7, 31),
+    (1999, 1, 32),
+    (2006, 1, 33),
+    (2009, 1, 34),
+    (2012, 7, 35),
+    (2015, 7, 36),
+    (2017, 1, 37),
+];
+
+fn mjd_to_j2000_seconds(mjd: i64) -> i64 {
+    (mjd - 51_544) * SECONDS_PER_DAY - SECONDS_PER_DAY / 2
+}
+
+impl LeapSecondTable {
+    /// The table built into the crate.
+    pub fn embedded() -> &'static LeapSecondTable {
+        static TABLE: OnceLock<LeapSecondTable> = OnceLock::new();
+
+        TABLE.get_or_init(|| LeapSecondTable {
+            entries: LEAP_SECONDS
+                .iter()
+                .map(|&(year, month, offset)| {
+                    let start = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap();
+                    (start.timestamp() - J2000_UNIX, offset)
+                })
+                .collect(),
+        })
+    }
+
+    /// Parse a leap second file.
+    ///
+    /// Accepts the IERS `Leap_Second.dat` format (`MJD day month year TAI-UTC`) and the
+    /// NIST/IETF `leap-seconds.list` format (`NTP-seconds TAI-UTC`). Lines starting
+    /// with `#` and trailing comments are ignored.
+    pub fn parse(text: &str) -> Result<LeapSecondTable, LeapSecondError> {
+        let mut entries = vec![];
+
+        for (number, line) in text.lines().enumerate() {
+            let data = line.split('#').next().unwrap_or("");
+            let fields = data.split_whitespace().collect::<Vec<&str>>();
+
+            let entry = match fields.len() {
+                0 => continue,
+                2 => fields[0].parse::<i64>().ok().and_then(|ntp| {
+                    let offset = fields[1].parse::<i64>().ok()?;
+                    let mjd = NTP_MJD + ntp.div_euclid(SECONDS_PER_DAY);
+                    Some((mjd_to_j2000_seconds(mjd), offset))
+                }),
+                5 => fields[0].parse::<f64>().ok().and_then(|mjd| {
+                    let offset = fields[4].parse::<i64>().ok()?;
+                    Some((mjd_to_j2000_seconds(mjd.floor() as i64), offset))
+                }),
+                _ => None,
+            };
+
+            match entry {
+                Some(entry) => entries.push(entry),
+                None => return Err(LeapSecondError::InvalidLine(number + 1, line.to_string())),
+            }
+        }
+
+        if entries.is_empty() {
+            return Err(LeapSecondError::Empty);
+        }
+
+        entries.sort();
+        Ok(LeapSecondTable { entries })
+    }
+
+    /// Read and parse a leap second file from disk.
+    pub fn from_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<LeapSecondTable, LeapSecondError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => LeapSecondTable::parse(&text),
+            Err(err) => Err(LeapSecondError::Io(err.to_string())),
+        }
+    }
+
+    /// TAI - UTC in seconds at a UTC time given as seconds since J2000.
+    ///
+    /// Dates before the first entry use its offset; UTC before 1972 was not offset from
+    /// TAI by whole seconds.
+    fn tai_minus_utc(&self, utc_seconds: i64) -> i64 {
+        let after = self
+            .entries
+            .partition_point(|&(start, _)| start <= utc_seconds);
+        self.entries[after.saturating_sub(1)].1
+    }
+}
+
+impl Default for LeapSecondTable {
+    fn default() -> LeapSecondTable {
+        LeapSecondTable::embedded().clone()
+    }
+}
+
+/// An instant, as the reading of a clock in a given time scale.
+///
+/// Stored as whole seconds and nanoseconds since 2000-01-01 12:00:00 on that scale, so
+/// nanosecond precision is kept. For UTC the count skips leap seconds, like julian dates
+/// and unix time do.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Epoch {
+    scale: TimeScale,
+    seconds: i64,
+    nanos: u32,
+}
+
+impl Epoch {
+    /// Epoch from seconds and nanoseconds since 2000-01-01 12:00:00 on `scale`.
+    pub fn new(scale: TimeScale, seconds: i64, nanos: i64) -> Epoch {
+        Epoch::from_nanos(
+            scale,
+            seconds as i128 * NANOS_PER_SECOND as i128 + nanos as i128,
+        )
+    }
+
+    fn from_nanos(scale: TimeScale, nanos: i128) -> Epoch {
+        let per_second = NANOS_PER_SECOND as i128;
+        Epoch {
+            scale,
+            seconds: nanos.div_euclid(per_second) as i64,
+            nanos: nanos.rem_euclid(per_second) as u32,
+        }
+    }
+
+    fn total_nanos(&self) -> i128 {
+        self.seconds as i128 * NANOS_PER_SECOND as i128 + self.nanos as i128
+    }
+
+    /// Epoch whose calendar reading on `scale` is `datetime`.
+    ///
+    /// `Epoch::from_datetime(t, TimeScale::Gps)` is the instant a GPS clock shows `t`.
+    pub fn from_datetime(datetime: DateTime<Utc>, scale: TimeScale) -> Epoch {
+        // chrono counts a leap second as an extra second of nanoseconds.
+        Epoch::new(
+            scale,
+            datetime.timestamp() - J2000_UNIX,
+            datetime.timestamp_subsec_nanos() as i64,
+        )
+    }
+
+    /// Epoch from a julian date on `scale`.
+    pub fn from_jd(jd: f64, scale: TimeScale) -> Epoch {
+        let days = jd - J2000_JD;
+        let whole = days.floor();
+        let nanos = ((days - whole) * (SECONDS_PER_DAY * NANOS_PER_SECOND) as f64).round() as i128;
+        Epoch::from_nanos(
+            scale,
+            whole as i128 * (SECONDS_PER_DAY * NANOS_PER_SECOND) as i128 + nanos,
+        )
+    }
+
+    pub fn scale(&self) -> TimeScale {
+        self.scale
+    }
+
+    /// Calendar reading on this epoch's scale, `None` outside the range of `DateTime`.
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        let seconds = self.seconds.checked_add(J2000_UNIX)?;
+        Utc.timestamp_opt(seconds, self.nanos).single()
+    }
+
+    /// Julian date on this epoch's scale.
+    pub fn jd(&self) -> f64 {
+        let (whole, fraction) = self.jd_split();
+        whole + fraction
+    }
+
+    /// Julian date split into a whole day number (at noon) and the fraction of day since.
+    ///
+    /// Differences between split dates keep sub-microsecond precision that a single
+    /// `f64` julian date cannot hold.
+    pub fn jd_split(&self) -> (f64, f64) {
+        let days = self.seconds.div_euclid(SECONDS_PER_DAY);
+        let seconds = self.seconds.rem_euclid(SECONDS_PER_DAY) as f64 + self.nanos as f64 / 1e9;
+        (J2000_JD + days as f64, seconds / SECONDS_PER_DAY as f64)
+    }
+
+    /// Modified julian date on this epoch's scale.
+    pub fn mjd(&self) -> f64 {
+        J2000_MJD + (self.seconds as f64 + self.nanos as f64 / 1e9) / SECONDS_PER_DAY as f64
+    }
+
+    /// Convert to another scale with the embedded leap second table and DUT1 = 0.
+    pub fn to_scale(&self, scale: TimeScale) -> Epoch {
+        self.to_scale_with(scale, LeapSecondTable::embedded(), 0.0)
+    }
+
+    /// Convert to another scale.
+    ///
+    /// `dut1` is UT1 - UTC in seconds, used when converting to or from UT1.
+    pub fn to_scale_with(
+        &self,
+        scale: TimeScale,
+        leap_seconds: &LeapSecondTable,
+        dut1: f64,
+    ) -> Epoch {
+        if scale == self.scale {
+            return *self;
+        }
+
+        let dut1 = (dut1 * 1e9).round() as i128;
+        let leap = |utc: i128| {
+            leap_seconds.tai_minus_utc(utc.div_euclid(NANOS_PER_SECOND as i128) as i64) as i128
+                * NANOS_PER_SECOND as i128
+        };
+
+        let t = self.total_nanos();
+        let tai = match self.scale {
+            TimeScale::Tai => t,
+            TimeScale::Tt => t - TT_MINUS_TAI as i128,
+            TimeScale::Gps => t + TAI_MINUS_GPS as i128,
+            TimeScale::Utc => t + leap(t),
+            TimeScale::Ut1 => t - dut1 + leap(t - dut1),
+        };
+
+        // the offset is looked up at the UTC time, which is found from a first guess.
+        let utc = || {
+            let guess = tai - leap(tai);
+            tai - leap(guess)
+        };
+
+        let converted = match scale {
+            TimeScale::Tai => tai,
+            TimeScale::Tt => tai + TT_MINUS_TAI as i128,
+            TimeScale::Gps => tai - TAI_MINUS_GPS as i128,
+            TimeScale::Utc => utc(),
+            TimeScale::Ut1 => utc() + dut1,
+        };
+
+        Epoch::from_nanos(scale, converted)
+    }
+
+    /// Seconds from `other` to this epoch, both taken on this epoch's scale.
+    pub fn seconds_since(&self, other: &Epoch) -> f64 {
+        let other = other.to_scale(self.scale);
+        (self.total_nanos() - other.total_nanos()) as f64 / 1e9
+    }
+}
+
+impl From<DateTime<Utc>> for Epoch {
+    fn from(datetime: DateTime<Utc>) -> Epoch {
+        Epoch::from_datetime(datetime, TimeScale::Utc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_diff;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> Epoch {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_offsets() {
+        let t = utc(2020, 6, 1, 0, 0, 0);
+
+        let tai = t.to_scale(TimeScale::Tai);
+        assert_eq!(
+            tai.to_datetime().unwrap(),
+            Utc.with_ymd_and_hms(2020, 6, 1, 0, 0, 37).unwrap()
+        );
+        assert_eq!(
+            t.to_scale(TimeScale::Gps)
+                .seconds_since(&Epoch::from_datetime(
+                    Utc.with_ymd_and_hms(2020, 6, 1, 0, 0, 18).unwrap(),
+                    TimeScale::Gps
+                )),
+            0.0
+        );
+        assert_eq!(
+            t.to_scale(TimeScale::Tt).to_datetime().unwrap(),
+            Utc.with_ymd_and_hms(2020, 6, 1, 0, 1, 9).unwrap()
+                + chrono::Duration::milliseconds(184)
+        );
+        assert_eq!(tai.to_scale(TimeScale::Utc), t);
+
+        // before and after the 2016-12-31 leap second.
+        assert_eq!(
+            utc(2016, 12, 31, 23, 59, 59)
+                .to_scale(TimeScale::Tai)
+                .seconds,
+            utc(2016, 12, 31, 23, 59, 59).seconds + 36
+        );
+        assert_eq!(
+            utc(2017, 1, 1, 0, 0, 0).to_scale(TimeScale::Tai).seconds,
+            utc(2017, 1, 1, 0, 0, 0).seconds + 37
+        );
+        assert_eq!(
+            utc(1980, 1, 6, 0, 0, 0)
+                .to_scale(TimeScale::Gps)
+                .to_datetime()
+                .unwrap(),
+            Utc.with_ymd_and_hms(1980, 1, 6, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ut1() {
+        let t = utc(2020, 6, 1, 0, 0, 0);
+        let ut1 = t.to_scale_with(TimeScale::Ut1, LeapSecondTable::embedded(), -0.2);
+
+        assert_eq!(
+            ut1.to_datetime().unwrap(),
+            Utc.with_ymd_and_hms(2020, 5, 31, 23, 59, 59).unwrap()
+                + chrono::Duration::milliseconds(800)
+        );
+        assert_eq!(
+            ut1.to_scale_with(TimeScale::Utc, LeapSecondTable::embedded(), -0.2),
+            t
+        );
+        assert_eq!(
+            ut1.to_scale_with(TimeScale::Tt, LeapSecondTable::embedded(), -0.2),
+            t.to_scale(TimeScale::Tt)
+        );
+    }
+
+    #[test]
+    fn test_nanoseconds() {
+        let datetime = Utc.with_ymd_and_hms(2006, 6, 25, 12, 0, 0).unwrap()
+            + chrono::Duration::nanoseconds(123_456_789);
+        let epoch = Epoch::from(datetime);
+
+        assert_eq!(epoch.to_datetime().unwrap(), datetime);
+        assert_eq!(Epoch::from_jd(1e12, TimeScale::Utc).to_datetime(), None);
+        assert_eq!(
+            epoch
+                .to_scale(TimeScale::Tt)
+                .to_scale(TimeScale::Utc)
+                .to_datetime()
+                .unwrap(),
+            datetime
+        );
+
+        let (whole, fraction) = epoch.jd_split();
+        assert_eq!(whole, 2453912.0);
+        assert_diff(fraction * 86400.0, 0.123456789, 1e-9);
+        assert_diff(epoch.mjd(), 53911.5 + 0.123456789 / 86400.0, 1e-10);
+        assert_diff(
+            Epoch::from_jd(epoch.jd(), TimeScale::Utc).seconds_since(&epoch),
+            0.0,
+            1e-4,
+        );
+
+        assert_eq!(
+            Epoch::new(TimeScale::Tai, 10, -1),
+            Epoch::new(TimeScale::Tai, 9, 999_999_999)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let iers = "#  Value of TAI-UTC in second valid beetween the initial value until\n\
+                    #    MJD        Date        TAI-UTC (s)\n\
+                    #           day month year\n\
+                    41317.0    1  1 1972       10\n\
+                    41499.0    1  7 1972       11\n\
+                    57754.0    1  1 2017       37\n";
+        let nist = "#$\t 3676924800\n\
+                    #@\t 3896899200\n\
+                    2272060800\t10\t# 1 Jan 1972\n\
+                    2287785600\t11\t# 1 Jul 1972\n\
+                    3692217600\t37\t# 1 Jan 2017\n";
+
+        let iers = LeapSecondTable::parse(iers).unwrap();
+        let nist = LeapSecondTable::parse(nist).unwrap();
+        assert_eq!(iers, nist);
+
+        let embedded = LeapSecondTable::embedded();
+        for table in [&iers, &nist] {
+            assert_eq!(table.entries[0], embedded.entries[0]);
+            assert_eq!(table.entries[1], embedded.entries[1]);
+            assert_eq!(table.entries[2], embedded.entries[27]);
+        }
+
+        // a table with a future leap second.
+        let updated = LeapSecondTable::parse(
+            "57754.0    1  1 2017       37\n61771.0    1  1 2028       38\n",
+        )
+        .unwrap();
+        let t = utc(2028, 6, 1, 0, 0, 0);
+        assert_eq!(
+            t.to_scale_with(TimeScale::Tai, &updated, 0.0)
+                .seconds_since(&t.to_scale(TimeScale::Tai)),
+            1.0
+        );
+
+        assert_eq!(
+            LeapSecondTable::parse("# nothing\n"),
+            Err(LeapSecondError::Empty)
+        );
+        assert_eq!(
+            LeapSecondTable::parse("41317.0    1  1 1972\n"),
+            Err(LeapSecondError::InvalidLine(
+                1,
+                "41317.0    1  1 1972".to_string()
+            ))
+        );
+        assert!(matches!(
+            LeapSecondTable::from_file("/nonexistent/leap-seconds.list"),
+            Err(LeapSecondError::Io(_))
+        ));
+    }
+}
@@ -1,8 +1,13 @@
 use crate::constants::*;
+use crate::eop::EopTable;
+use crate::frames::{FrameVector, Itrf, State, Teme};
+use crate::time::Epoch;
 use crate::*;
 use crate::Bearing;
+use crate::BearingRate;
 use crate::Geodedic;
 use crate::TopoCentric;
+use crate::Mat3;
 use crate::Vec3;
 
 pub fn radians_to_degrees(radians: f64) -> f64 {
@@ -45,95 +50,115 @@ pub fn radians_long(degrees: f64) -> f64 {
     degrees_to_radians(degrees)
 }
 
-pub fn geodedic_to_ecf(geodetic: &Geodedic) -> Ecf {
-    const A: f64 = 6378.137;
-    const B: f64 = 6356.7523142;
-    const F: f64 = (A - B) / A;
-    const E2: f64 = ((2.0 * F) - (F * F));
-
-    let normal = A / (1.0 - (E2 * geodetic.latitude.sin().powf(2.0))).sqrt();
-
-    let x = (normal + geodetic.height) * geodetic.latitude.cos() * geodetic.longitude.cos();
-    let y = (normal + geodetic.height) * geodetic.latitude.cos() * geodetic.longitude.sin();
-    let z = ((normal * (1.0 - E2)) + geodetic.height) * geodetic.latitude.sin();
+/// WGS-84 with the polar radius rounded to 6356.7523142 km, as these conversions have
+/// always used. Use `Ellipsoid::WGS84` with the `_with` functions for the exact one.
+const LEGACY_WGS84: Ellipsoid = Ellipsoid {
+    a: 6378.137,
+    f: (6378.137 - 6356.7523142) / 6378.137,
+};
 
-    Vec3 { x, y, z }
+/// Geodetic position on the WGS-84 ellipsoid, with the legacy rounded polar radius, to
+/// ECF.
+pub fn geodedic_to_ecf(geodetic: &Geodedic) -> Ecf {
+    geodedic_to_ecf_with(geodetic, &LEGACY_WGS84)
 }
 
-pub fn eci_to_geodedic(eci: &Eci, gmst: f64) -> Geodedic {
-    const A: f64 = 6378.137;
-    const B: f64 = 6356.7523142;
-    let r: f64 = ((eci.x * eci.x) + (eci.y * eci.y)).sqrt();
-    const F: f64 = (A - B) / A;
-    const E2: f64 = ((2.0 * F) - (F * F));
-
-    let mut longitude = eci.y.atan2(eci.x) - gmst;
-
-    while longitude < -PI {
-        longitude += TWO_PI;
-    }
-
-    while longitude > PI {
-        longitude -= TWO_PI;
-    }
-
-    const KMAX: i32 = 20;
+/// Geodetic position on `ellipsoid` to ECF.
+pub fn geodedic_to_ecf_with(geodetic: &Geodedic, ellipsoid: &Ellipsoid) -> Ecf {
+    let e2 = ellipsoid.e2();
+    let (sin_lat, cos_lat) = geodetic.latitude.sin_cos();
+    let (sin_lon, cos_lon) = geodetic.longitude.sin_cos();
 
-    let mut latitude = eci.z.atan2((eci.x.powi(2) + eci.y.powi(2)).sqrt());
+    let normal = ellipsoid.a / (1.0 - (e2 * sin_lat * sin_lat)).sqrt();
 
-    let mut k: i32 = 0;
-    let mut c: f64 = 0.0;
+    let x = (normal + geodetic.height) * cos_lat * cos_lon;
+    let y = (normal + geodetic.height) * cos_lat * sin_lon;
+    let z = ((normal * (1.0 - e2)) + geodetic.height) * sin_lat;
 
-    while k < KMAX {
-        c = 1.0 / (1.0 - (E2 * latitude.sin().powi(2)));
-        latitude = (eci.z + (A * c * E2 * latitude.sin())).atan2(r);
-        k += 1;
-    }
+    Vec3 { x, y, z }
+}
 
-    let height = (r / latitude.cos()) - (A * c);
+/// ECF position to geodetic on `ellipsoid`.
+///
+/// Uses Vermeille's closed form (J. Geodesy 76, 2002), which is exact up to rounding:
+/// round trips agree to better than 1e-9 km in height and 1e-14 rad in latitude, from
+/// the surface out past geostationary distances. The form needs the point to be outside
+/// the evolute of the ellipse, which is within `a * e2` (43 km for WGS-84) of the
+/// center; closer points give NaN.
+pub fn ecf_to_geodedic(ecf: &Ecf, ellipsoid: &Ellipsoid) -> Geodedic {
+    let a2 = ellipsoid.a * ellipsoid.a;
+    let e2 = ellipsoid.e2();
+    let e4 = e2 * e2;
+
+    let rho2 = (ecf.x * ecf.x) + (ecf.y * ecf.y);
+    let p = rho2 / a2;
+    let q = (1.0 - e2) * (ecf.z * ecf.z) / a2;
+    let r = (p + q - e4) / 6.0;
+    let s = e4 * p * q / (4.0 * r * r * r);
+    let t = (1.0 + s + (s * (2.0 + s)).sqrt()).cbrt();
+    let u = r * (1.0 + t + (1.0 / t));
+    let v = ((u * u) + (e4 * q)).sqrt();
+    let w = e2 * (u + v - q) / (2.0 * v);
+    let k = (u + v + (w * w)).sqrt() - w;
+    let d = k * rho2.sqrt() / (k + e2);
+    let dz = ((d * d) + (ecf.z * ecf.z)).sqrt();
 
     Geodedic {
-        latitude,
-        longitude,
-        height,
+        latitude: 2.0 * ecf.z.atan2(d + dz),
+        longitude: ecf.y.atan2(ecf.x),
+        height: (k + e2 - 1.0) / k * dz,
     }
 }
 
-pub fn ecf_to_eci(ecf: &Ecf, gmst: f64) -> Eci {
-    let x = (ecf.x * gmst.cos()) - (ecf.y * gmst.sin());
-    let y = (ecf.x * gmst.sin()) + (ecf.y * gmst.cos());
-    let z = ecf.z;
+/// ECI position to geodetic on the WGS-84 ellipsoid, with the legacy rounded polar
+/// radius, with the earth rotated by `gmst`.
+pub fn eci_to_geodedic(eci: &Eci, gmst: f64) -> Geodedic {
+    ecf_to_geodedic(&eci_to_ecf(eci, gmst), &LEGACY_WGS84)
+}
 
-    Vec3 { x, y, z }
+pub fn ecf_to_eci(ecf: &Ecf, gmst: f64) -> Eci {
+    Mat3::rotation_z(gmst).transpose() * *ecf
 }
 
 pub fn eci_to_ecf(eci: &Eci, gmst: f64) -> Ecf {
-    let x = (eci.x * gmst.cos()) + (eci.y * gmst.sin());
-    let y = (eci.x * -gmst.sin()) + (eci.y * gmst.cos());
-    let z = eci.z;
-
-    Vec3 { x, y, z }
+    Mat3::rotation_z(gmst) * *eci
 }
 
-pub fn topocentric(observer: &Geodedic, satellite: &Ecf) -> TopoCentric {
-    let observer_ecf = geodedic_to_ecf(&observer);
+/// Convert a TEME position (as returned by SGP4) to ITRF at `epoch`.
+///
+/// With an EOP table, GMST uses UT1 - UTC and polar motion is applied. Without one, or
+/// outside the table, both are zero and this is `eci_to_ecf` at the GMST of `epoch`.
+/// Velocities also need the rotation of the earth; convert them with
+/// [`State::to_itrf`](crate::frames::State).
+pub fn teme_to_itrf(teme: &Eci, epoch: Epoch, eop: Option<&EopTable>) -> Ecf {
+    FrameVector::<Teme>::from_vec3(teme).to_itrf(epoch, eop).to_vec3()
+}
 
-    let r = satellite.subtract(&observer_ecf);
+/// Convert an ITRF position to TEME at `epoch`, the inverse of [`teme_to_itrf`].
+pub fn itrf_to_teme(itrf: &Ecf, epoch: Epoch, eop: Option<&EopTable>) -> Eci {
+    FrameVector::<Itrf>::from_vec3(itrf).to_teme(epoch, eop).to_vec3()
+}
 
-    let top_s = (observer.latitude.sin() * observer.longitude.cos() * r.x)
-        + (observer.latitude.sin() * observer.longitude.sin() * r.y)
-        - (observer.latitude.cos() * r.z);
+/// Rotation from ECF to the observer's south, east, zenith axes.
+fn sez_rotation(observer: &Geodedic) -> Mat3 {
+    let (sin_lat, cos_lat) = observer.latitude.sin_cos();
+    let (sin_lon, cos_lon) = observer.longitude.sin_cos();
 
-    let top_e = (-observer.longitude.sin() * r.x) + (observer.longitude.cos() * r.y);
+    Mat3([
+        [sin_lat * cos_lon, sin_lat * sin_lon, -cos_lat],
+        [-sin_lon, cos_lon, 0.0],
+        [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+    ])
+}
 
-    let top_z = (observer.latitude.cos() * observer.longitude.cos() * r.x)
-        + (observer.latitude.cos() * observer.longitude.sin() * r.y)
-        + (observer.latitude.sin() * r.z);
+pub fn topocentric(observer: &Geodedic, satellite: &Ecf) -> TopoCentric {
+    let r = *satellite - geodedic_to_ecf(observer);
+    let sez = sez_rotation(observer) * r;
 
     TopoCentric {
-        top_s,
-        top_e,
-        top_z,
+        top_s: sez.x,
+        top_e: sez.y,
+        top_z: sez.z,
     }
 }
 
@@ -155,6 +180,66 @@ pub fn ecf_to_look_angles(observer: &Geodedic, satellite: &Ecf) -> Bearing {
     topocentric_to_look_angles(&topocentric)
 }
 
+/// Velocity relative to the observer in south, east, zenith components, from an ECF
+/// velocity in km/s.
+pub fn topocentric_rate(observer: &Geodedic, velocity: &Ecf) -> TopoCentric {
+    let sez = sez_rotation(observer) * *velocity;
+
+    TopoCentric {
+        top_s: sez.x,
+        top_e: sez.y,
+        top_z: sez.z,
+    }
+}
+
+/// Rates of the look angles from a topocentric position and its rate.
+///
+/// The azimuth rate is undefined at the zenith.
+pub fn topocentric_to_look_angle_rates(tc: &TopoCentric, rate: &TopoCentric) -> BearingRate {
+    let (s, e, z) = (tc.top_s, tc.top_e, tc.top_z);
+    let (ds, de, dz) = (rate.top_s, rate.top_e, rate.top_z);
+
+    let horizontal_sq = s * s + e * e;
+    let horizontal = horizontal_sq.sqrt();
+    let range_sq = horizontal_sq + z * z;
+    let range = range_sq.sqrt();
+
+    let horizontal_rate = (s * ds + e * de) / horizontal;
+
+    BearingRate {
+        azimuth: (e * ds - s * de) / horizontal_sq,
+        elevation: (horizontal * dz - z * horizontal_rate) / range_sq,
+        range: (s * ds + e * de + z * dz) / range,
+    }
+}
+
+/// Look angles and their rates from an ECF position (km) and velocity (km/s).
+pub fn ecf_to_look_angle_rates(
+    observer: &Geodedic,
+    position: &Ecf,
+    velocity: &Ecf,
+) -> (Bearing, BearingRate) {
+    let tc = topocentric(observer, position);
+    let rate = topocentric_rate(observer, velocity);
+
+    (topocentric_to_look_angles(&tc), topocentric_to_look_angle_rates(&tc, &rate))
+}
+
+/// Look angles and their rates from a TEME state, as returned by SGP4, at `epoch`.
+///
+/// The state is converted to ITRF with [`State::to_itrf`], which accounts for the rotation
+/// of the earth under the satellite and applies the EOP table when given.
+pub fn teme_to_look_angle_rates(
+    observer: &Geodedic,
+    state: &State<Teme>,
+    epoch: Epoch,
+    eop: Option<&EopTable>,
+) -> (Bearing, BearingRate) {
+    let itrf = state.to_itrf(epoch, eop);
+
+    ecf_to_look_angle_rates(observer, &itrf.position.to_vec3(), &itrf.velocity.to_vec3())
+}
+
 #[cfg(test)]
 mod test {
     use crate::tests::*;
@@ -232,4 +317,157 @@ mod test {
         assert_diff(ts.top_e, 5154.118963234977, 1e-12);
         assert_diff(ts.top_z, -2262.429067309148, 1e-12);
     }
+
+    #[test]
+    fn teme_to_itrf() {
+        use crate::eop::{EopEntry, EopTable};
+        use crate::time::{Epoch, TimeScale};
+        use chrono::{Duration, TimeZone, Utc};
+
+        // Vallado, "Revisiting Spacetrack Report #3", 2004-04-06 07:51:28.386009 UTC.
+        let epoch = Epoch::from_datetime(
+            Utc.with_ymd_and_hms(2004, 4, 6, 7, 51, 28).unwrap() + Duration::nanoseconds(386_009_000),
+            TimeScale::Utc,
+        );
+        let orientation = EopEntry { xp: -0.140682, yp: 0.333309, dut1: -0.4399619, ..EopEntry::default() };
+        let eop = EopTable::new(vec![
+            EopEntry { mjd: 53101.0, ..orientation },
+            EopEntry { mjd: 53102.0, ..orientation },
+        ])
+        .unwrap();
+
+        let teme = Eci {
+            x: 5094.18016210,
+            y: 6127.64465950,
+            z: 6380.34453270,
+        };
+
+        let itrf = transforms::teme_to_itrf(&teme, epoch, Some(&eop));
+        assert_diff(itrf.x, -1033.4793830, 1e-6);
+        assert_diff(itrf.y, 7901.2952754, 1e-6);
+        assert_diff(itrf.z, 6380.3565958, 1e-6);
+
+        let back = transforms::itrf_to_teme(&itrf, epoch, Some(&eop));
+        assert!(back.range(&teme) < 1e-9);
+
+        let gmst = propogation::gstime::gstime_datetime(epoch);
+        assert_eq!(transforms::teme_to_itrf(&teme, epoch, None), transforms::eci_to_ecf(&teme, gmst));
+    }
+
+    #[test]
+    fn ecf_to_geodedic() {
+        use crate::constants::Ellipsoid;
+
+        // Vallado example 3-3.
+        let ecf = Ecf {
+            x: 6524.834,
+            y: 6862.875,
+            z: 6448.296,
+        };
+        let geodetic = transforms::ecf_to_geodedic(&ecf, &Ellipsoid::WGS84);
+        assert_diff(geodetic.latitude * constants::RAD_TO_DEG, 34.352496, 1e-6);
+        assert_diff(geodetic.longitude * constants::RAD_TO_DEG, 46.4464, 1e-4);
+        assert_diff(geodetic.height, 5085.22, 1e-2);
+
+        for ellipsoid in &[Ellipsoid::WGS84, Ellipsoid::WGS72, Ellipsoid::GRS80, Ellipsoid::new(6378.0, 1.0 / 150.0)] {
+            for &height in &[-5.0, 0.0, 0.370, 400.0, 20_200.0, 35_786.0, 400_000.0] {
+                for latitude in (-90i32..=90).step_by(5) {
+                    let geodetic = Geodedic {
+                        longitude: -122.0308 * constants::DEG_2_RAD,
+                        latitude: latitude as f64 * constants::DEG_2_RAD,
+                        height,
+                    };
+
+                    let ecf = transforms::geodedic_to_ecf_with(&geodetic, ellipsoid);
+                    let back = transforms::ecf_to_geodedic(&ecf, ellipsoid);
+                    assert_diff(back.latitude, geodetic.latitude, 1e-14);
+                    assert_diff(back.height, geodetic.height, 1e-9);
+                    if latitude.abs() != 90 {
+                        assert_diff(back.longitude, geodetic.longitude, 1e-14);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eci_to_geodedic() {
+        let observer = Geodedic {
+            longitude: -122.0308 * constants::DEG_2_RAD,
+            latitude: 36.9613422 * constants::DEG_2_RAD,
+            height: 0.370,
+        };
+
+        let gmst = 1.265125075734467;
+        let eci = transforms::ecf_to_eci(&transforms::geodedic_to_ecf(&observer), gmst);
+        let geodetic = transforms::eci_to_geodedic(&eci, gmst);
+
+        assert_diff(geodetic.latitude, observer.latitude, 1e-14);
+        assert_diff(geodetic.longitude, observer.longitude, 1e-14);
+        assert_diff(geodetic.height, observer.height, 1e-9);
+    }
+
+    #[test]
+    fn look_angle_rates() {
+        let observer = Geodedic {
+            longitude: -122.0308 * constants::DEG_2_RAD,
+            latitude: 36.9613422 * constants::DEG_2_RAD,
+            height: 0.370,
+        };
+
+        // straight-line motion past the observer, against central differences
+        let start = transforms::geodedic_to_ecf(&observer) + Vec3::new(-900.0, 300.0, 700.0);
+        let velocity = Vec3::new(2.0, -6.5, 1.5);
+        let look_angles = |t: f64| transforms::ecf_to_look_angles(&observer, &(start + t * velocity));
+
+        for &t in &[0.0, 60.0, 150.0, 300.0] {
+            let position = start + t * velocity;
+            let (bearing, rates) = transforms::ecf_to_look_angle_rates(&observer, &position, &velocity);
+            assert_eq!(bearing, look_angles(t));
+
+            let dt = 1e-3;
+            let (before, after) = (look_angles(t - dt), look_angles(t + dt));
+            assert_diff(rates.range, (after.range - before.range) / (2.0 * dt), 1e-9);
+            assert_diff(rates.azimuth, (after.azimuth - before.azimuth) / (2.0 * dt), 1e-11);
+            assert_diff(rates.elevation, (after.elevation - before.elevation) / (2.0 * dt), 1e-11);
+        }
+    }
+
+    #[test]
+    fn teme_to_look_angle_rates() {
+        use crate::time::Epoch;
+        use chrono::{Duration, TimeZone, Utc};
+
+        let satrec = io::twoline2satrec(
+            "1 25544U 98067A   19070.20068744  .00000619  00000-0  17310-4 0  9990",
+            "2 25544  51.6414 128.3903 0004102  93.2843   5.7821 15.52799004160030",
+        )
+        .unwrap();
+        let observer = Geodedic {
+            longitude: -122.0308 * constants::DEG_2_RAD,
+            latitude: 36.9613422 * constants::DEG_2_RAD,
+            height: 0.370,
+        };
+
+        let look_angles = |datetime| {
+            let epoch = Epoch::from(datetime);
+            let state = propogation::propogate_datetime(&satrec, epoch).unwrap().teme();
+            transforms::teme_to_look_angle_rates(&observer, &state, epoch, None)
+        };
+
+        let datetime = Utc.with_ymd_and_hms(2019, 3, 11, 9, 40, 0).unwrap();
+        let (bearing, rates) = look_angles(datetime);
+
+        let epoch = Epoch::from(datetime);
+        let position = propogation::propogate_datetime(&satrec, epoch).unwrap().position;
+        let itrf = transforms::teme_to_itrf(&position, epoch, None);
+        assert_eq!(bearing, transforms::ecf_to_look_angles(&observer, &itrf));
+
+        // SGP4 velocities differ from the change in its positions by a few mm/s.
+        let (before, _) = look_angles(datetime - Duration::milliseconds(100));
+        let (after, _) = look_angles(datetime + Duration::milliseconds(100));
+        assert_diff(rates.range, (after.range - before.range) / 0.2, 1e-5);
+        assert_diff(rates.azimuth, (after.azimuth - before.azimuth) / 0.2, 1e-8);
+        assert_diff(rates.elevation, (after.elevation - before.elevation) / 0.2, 1e-8);
+    }
 }